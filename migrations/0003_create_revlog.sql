@@ -0,0 +1,19 @@
+-- Create the review log table, one row per graded review.
+PRAGMA foreign_keys = ON;
+
+CREATE TABLE IF NOT EXISTS revlog (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    card_hash TEXT NOT NULL,
+    reviewed_at TEXT NOT NULL,
+    grade INTEGER NOT NULL,
+    elapsed_days REAL NOT NULL,
+    previous_stability REAL,
+    previous_difficulty REAL,
+    stability REAL NOT NULL,
+    difficulty REAL NOT NULL,
+    interval_raw REAL NOT NULL,
+    time_spent_ms INTEGER NOT NULL
+) STRICT;
+
+CREATE INDEX IF NOT EXISTS idx_revlog_card_hash ON revlog(card_hash);
+CREATE INDEX IF NOT EXISTS idx_revlog_reviewed_at ON revlog(reviewed_at);
@@ -0,0 +1,9 @@
+-- Create the table holding personalized FSRS parameters.
+PRAGMA foreign_keys = ON;
+
+CREATE TABLE IF NOT EXISTS fsrs_parameters (
+    id INTEGER PRIMARY KEY,
+    parameters TEXT NOT NULL,
+    review_count INTEGER NOT NULL,
+    optimized_at TEXT NOT NULL
+) STRICT;
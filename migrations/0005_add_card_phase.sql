@@ -0,0 +1,12 @@
+-- Track where each card is in its learning or relearning steps.
+PRAGMA foreign_keys = ON;
+
+ALTER TABLE cards ADD COLUMN phase TEXT NOT NULL DEFAULT 'learning'
+    CHECK (phase IN ('learning', 'review', 'relearning'));
+ALTER TABLE cards ADD COLUMN step INTEGER NOT NULL DEFAULT 0;
+
+-- Cards used to graduate after their third review.
+UPDATE cards
+SET
+    phase = CASE WHEN review_count >= 3 THEN 'review' ELSE 'learning' END,
+    step = CASE WHEN review_count >= 3 THEN 0 ELSE review_count END;
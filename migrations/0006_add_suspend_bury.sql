@@ -0,0 +1,5 @@
+-- Let cards be kept out of drills: suspended until undone, or buried until a set time.
+PRAGMA foreign_keys = ON;
+
+ALTER TABLE cards ADD COLUMN suspended INTEGER NOT NULL DEFAULT 0 CHECK (suspended IN (0, 1));
+ALTER TABLE cards ADD COLUMN buried_until TEXT;
@@ -0,0 +1,21 @@
+-- Count lapses (a failed review right after a passing one) and flag leeches.
+PRAGMA foreign_keys = ON;
+
+ALTER TABLE cards ADD COLUMN lapses INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE cards ADD COLUMN leech INTEGER NOT NULL DEFAULT 0 CHECK (leech IN (0, 1));
+
+UPDATE cards
+SET lapses = (
+    SELECT COUNT(*)
+    FROM (
+        SELECT
+            grade,
+            LAG(grade) OVER (ORDER BY reviewed_at, id) AS previous_grade
+        FROM revlog
+        WHERE revlog.card_hash = cards.card_hash
+    )
+    WHERE grade = 1 AND previous_grade > 1
+);
+
+-- 8 is the default leech threshold.
+UPDATE cards SET leech = 1 WHERE lapses >= 8;
@@ -0,0 +1,4 @@
+-- Tell scheduling reviews apart from cram sessions, which leave cards' scheduling alone.
+PRAGMA foreign_keys = ON;
+
+ALTER TABLE revlog ADD COLUMN kind TEXT NOT NULL DEFAULT 'review' CHECK (kind IN ('review', 'cram'));
@@ -0,0 +1,8 @@
+-- Remember where each card was last seen and its text, so reworded cards can keep their history.
+PRAGMA foreign_keys = ON;
+
+ALTER TABLE cards ADD COLUMN file_path TEXT;
+ALTER TABLE cards ADD COLUMN file_line INTEGER;
+ALTER TABLE cards ADD COLUMN content TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_cards_file_path ON cards(file_path);
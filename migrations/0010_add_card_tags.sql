@@ -0,0 +1,4 @@
+-- Remember each card's tags, separated by spaces, as last seen in its file.
+PRAGMA foreign_keys = ON;
+
+ALTER TABLE cards ADD COLUMN tags TEXT NOT NULL DEFAULT '';
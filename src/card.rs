@@ -1,7 +1,8 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Result, bail};
 
+use crate::cloze_utils::mask_cloze_text;
 use crate::llm::drill_preprocessor::AIStatus;
 
 #[derive(Clone, Debug)]
@@ -12,6 +13,10 @@ pub struct Card {
     pub content: CardContent,
     pub card_hash: String,
     pub ai_status: AIStatus,
+    /// Which deletion of a multi-cloze card this is; `0` for every other card.
+    pub sibling: usize,
+    /// Lowercase tags from the card and its file's frontmatter, sorted. Not part of the hash.
+    pub tags: Vec<String>,
 }
 
 impl Card {
@@ -27,6 +32,86 @@ impl Card {
             content,
             card_hash,
             ai_status: AIStatus::NoNeed,
+            sibling: 0,
+            tags: Vec::new(),
+        }
+    }
+
+    /// `path:line` of the card's first line in its file.
+    pub fn location(&self) -> String {
+        format!(
+            "{}:{}",
+            self.file_path.display(),
+            self.file_card_range.0 + 1
+        )
+    }
+
+    /// Whether `query` names this card: a prefix of its hash, or text found
+    /// (case-insensitively) in its content or `path:line` location.
+    pub fn matches_query(&self, query: &str) -> bool {
+        let query = query.trim();
+        if query.is_empty() {
+            return false;
+        }
+        if self.card_hash.starts_with(query) {
+            return true;
+        }
+        let query = query.to_lowercase();
+        self.text().to_lowercase().contains(&query)
+            || self.location().to_lowercase().contains(&query)
+    }
+
+    /// Whether `query` names exactly this card: its full hash or id, or its `path:line`,
+    /// with the path given in full or relative to any parent directory.
+    pub fn is_named_by(&self, query: &str) -> bool {
+        let query = query.trim();
+        if query.is_empty() {
+            return false;
+        }
+        if self.card_hash == query {
+            return true;
+        }
+        let Some((path, line)) = query.rsplit_once(':') else {
+            return false;
+        };
+        if path.is_empty() || line.parse::<usize>().ok() != Some(self.file_card_range.0 + 1) {
+            return false;
+        }
+        let path = Path::new(path);
+        if path.is_absolute() {
+            return std::fs::canonicalize(&self.file_path).is_ok_and(|own| own == path);
+        }
+        self.file_path
+            .ends_with(path.strip_prefix("./").unwrap_or(path))
+    }
+
+    /// Whether `other` is another deletion of the same multi-cloze card.
+    pub fn is_sibling_of(&self, other: &Card) -> bool {
+        self.card_hash != other.card_hash
+            && self.file_path == other.file_path
+            && self.file_card_range == other.file_card_range
+    }
+
+    /// Text to recognise the card by after an edit: its content, with a cloze card's own
+    /// deletion masked so that siblings tell apart.
+    pub fn comparable_text(&self) -> String {
+        match &self.content {
+            CardContent::Cloze {
+                text,
+                cloze_range: Some(range),
+                ..
+            } => mask_cloze_text(text, range),
+            _ => self.text(),
+        }
+    }
+
+    /// The card's question and answer, or its cloze text.
+    pub fn text(&self) -> String {
+        match &self.content {
+            CardContent::Basic {
+                question, answer, ..
+            } => format!("{question}\n{answer}"),
+            CardContent::Cloze { text, .. } => text.clone(),
         }
     }
 }
@@ -36,13 +121,33 @@ pub enum CardContent {
     Basic {
         question: String,
         answer: String,
+        /// Shown on request before the answer, from an `H:` section.
+        hint: Option<String>,
+        /// Shown with the answer, from an `N:` section.
+        notes: Option<String>,
     },
     Cloze {
         text: String,
         cloze_range: Option<ClozeRange>,
+        hint: Option<String>,
+        notes: Option<String>,
     },
 }
 
+impl CardContent {
+    pub fn hint(&self) -> Option<&str> {
+        match self {
+            CardContent::Basic { hint, .. } | CardContent::Cloze { hint, .. } => hint.as_deref(),
+        }
+    }
+
+    pub fn notes(&self) -> Option<&str> {
+        match self {
+            CardContent::Basic { notes, .. } | CardContent::Cloze { notes, .. } => notes.as_deref(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ClozeRange {
     pub start: usize,
@@ -68,3 +173,49 @@ pub enum CardType {
     Basic,
     Cloze,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(content: CardContent) -> Card {
+        Card::new(
+            PathBuf::from("decks/geo.md"),
+            (4, 6),
+            content,
+            "7f3a9c".into(),
+        )
+    }
+
+    #[test]
+    fn matches_query_by_text_location_or_hash() {
+        let basic = card(CardContent::Basic {
+            question: "Capital of Japan?".into(),
+            answer: "Tokyo".into(),
+            hint: None,
+            notes: None,
+        });
+        assert!(basic.matches_query("tokyo"));
+        assert!(basic.matches_query("capital of"));
+        assert!(basic.matches_query("geo.md:5"));
+        assert!(basic.matches_query("7f3a"));
+        assert!(!basic.matches_query("Kyoto"));
+        assert!(!basic.matches_query("  "));
+
+        let cloze = card(CardContent::Cloze {
+            text: "[Paris] is in France".into(),
+            cloze_range: None,
+            hint: None,
+            notes: None,
+        });
+        assert!(cloze.matches_query("FRANCE"));
+        assert!(cloze.is_named_by("7f3a9c"));
+        assert!(cloze.is_named_by("geo.md:5"));
+        assert!(cloze.is_named_by("./decks/geo.md:5"));
+        assert!(!cloze.is_named_by("7f3a"));
+        assert!(!cloze.is_named_by("eo.md:5"));
+        assert!(!cloze.is_named_by("geo.md:6"));
+        assert!(!cloze.is_named_by("France"));
+        assert_eq!(cloze.location(), "decks/geo.md:5");
+    }
+}
@@ -1,34 +1,214 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use pulldown_cmark::{Event, Parser, Tag};
+
 use crate::card::ClozeRange;
 
+/// Byte ranges of the `[...]` deletions in `text`, outermost brackets only. Brackets in
+/// code spans, code blocks, links, images and inline HTML don't count, and neither do
+/// ones escaped as `\[` or `\]`. A `[` still open at a blank line or the end of the text
+/// is plain text, so it can't swallow the deletions after it.
 pub fn find_cloze_ranges(text: &str) -> Vec<(usize, usize)> {
-    let mut ranges = Vec::new();
-    let mut start = None;
+    let skipped = markdown_ranges(text);
+    let mut pairs = Vec::new();
+    let mut open = Vec::new();
+    let mut escaped = false;
+    let mut blank_line = true;
 
     for (i, ch) in text.char_indices() {
+        if skipped.iter().any(|range| range.contains(&i)) {
+            escaped = false;
+            blank_line = false;
+            continue;
+        }
+        if escaped {
+            escaped = false;
+            continue;
+        }
         match ch {
-            '[' if start.is_none() => start = Some(i),
+            '\n' => {
+                if blank_line {
+                    open.clear();
+                }
+                blank_line = true;
+                continue;
+            }
+            '\\' => escaped = true,
+            '[' => open.push(i),
             ']' => {
-                if let Some(s) = start.take() {
-                    let e = i + ch.len_utf8();
-                    ranges.push((s, e));
+                if let Some(start) = open.pop() {
+                    pairs.push((start, i + ch.len_utf8()));
                 }
             }
             _ => {}
         }
+        if !ch.is_whitespace() {
+            blank_line = false;
+        }
     }
 
+    let mut ranges: Vec<(usize, usize)> = pairs
+        .iter()
+        .copied()
+        .filter(|&(start, end)| {
+            !pairs
+                .iter()
+                .any(|&(outer_start, outer_end)| outer_start < start && end < outer_end)
+        })
+        .collect();
+    ranges.sort();
     ranges
 }
 
+/// Parts of `text` whose brackets are Markdown syntax or literal code, not deletions.
+fn markdown_ranges(text: &str) -> Vec<Range<usize>> {
+    Parser::new(text)
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(Tag::CodeBlock(_) | Tag::Link { .. } | Tag::Image { .. })
+            | Event::Code(_)
+            | Event::Html(_)
+            | Event::InlineHtml(_) => Some(range),
+            _ => None,
+        })
+        .collect()
+}
+
+/// One `[...]` deletion. `[c2: answer]` puts it in group 2, hidden together with every
+/// other `c2` deletion; `[answer | hint]` shows the hint in the blank. Only a `|` with a
+/// space on each side starts the hint, and `\|` is always a literal pipe.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClozeDeletion<'a> {
+    pub start: usize,
+    pub end: usize,
+    pub group: Option<u32>,
+    pub answer: &'a str,
+    pub hint: Option<&'a str>,
+}
+
+impl<'a> ClozeDeletion<'a> {
+    /// The answer as shown on the card, with `\|` written as `|`.
+    pub fn shown_answer(&self) -> Cow<'a, str> {
+        if self.answer.contains("\\|") {
+            Cow::Owned(self.answer.replace("\\|", "|"))
+        } else {
+            Cow::Borrowed(self.answer)
+        }
+    }
+
+    /// Whether this deletion is hidden on the card that tests `other`.
+    fn hidden_with(&self, other: &ClozeDeletion) -> bool {
+        match (self.group, other.group) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.start == other.start,
+        }
+    }
+}
+
+pub fn parse_deletions(text: &str) -> Vec<ClozeDeletion<'_>> {
+    find_cloze_ranges(text)
+        .into_iter()
+        .map(|(start, end)| parse_deletion(text, start, end))
+        .collect()
+}
+
+fn parse_deletion(text: &str, start: usize, end: usize) -> ClozeDeletion<'_> {
+    let mut inner = text[start..end]
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim();
+    let mut group = None;
+    if let Some((prefix, rest)) = inner.split_once(':')
+        && let Some(number) = prefix.trim().strip_prefix(['c', 'C'])
+        && let Ok(number) = number.parse::<u32>()
+    {
+        group = Some(number);
+        inner = rest.trim();
+    }
+    let mut hint = None;
+    // `[a|b]` and `[ls \| grep]` have no ` | `, so they stay the answer
+    if let Some((answer, after)) = inner.rsplit_once(" | ")
+        && !answer.trim().is_empty()
+        && !after.trim().is_empty()
+    {
+        hint = Some(after.trim());
+        inner = answer.trim();
+    }
+    ClozeDeletion {
+        start,
+        end,
+        group,
+        answer: inner,
+        hint,
+    }
+}
+
+/// The deletion each card of a cloze text starts at: one card per group, and one per
+/// ungrouped deletion, in order of appearance.
+pub fn cloze_card_starts<'a>(deletions: &'a [ClozeDeletion<'a>]) -> Vec<&'a ClozeDeletion<'a>> {
+    let mut starts: Vec<&ClozeDeletion> = Vec::new();
+    for deletion in deletions {
+        if !starts.iter().any(|start| deletion.hidden_with(start)) {
+            starts.push(deletion);
+        }
+    }
+    starts
+}
+
+/// `text` with the deletion at `range`, and the rest of its group, blanked out. Other
+/// deletions are shown as plain text.
 pub fn mask_cloze_text(text: &str, range: &ClozeRange) -> String {
-    let start = range.start;
-    let end = range.end;
-    let hidden_section = &text[start..end];
-    let core = hidden_section.trim_start_matches('[').trim_end_matches(']');
-    let placeholder = "_".repeat(core.chars().count().max(3));
-
-    let masked = format!("{}[{}]{}", &text[..start], placeholder, &text[end..]);
-    masked
+    render_cloze(text, range, |deletion| {
+        let blank = "_".repeat(deletion.shown_answer().chars().count().max(3));
+        match deletion.hint {
+            Some(hint) => format!("[{blank} | {hint}]"),
+            None => format!("[{blank}]"),
+        }
+    })
+}
+
+/// `text` with the deletions hidden on this card left in brackets and the others shown
+/// as plain text.
+pub fn reveal_cloze_text(text: &str, range: &ClozeRange) -> String {
+    render_cloze(text, range, |deletion| {
+        format!("[{}]", deletion.shown_answer())
+    })
+}
+
+fn render_cloze(
+    text: &str,
+    range: &ClozeRange,
+    hidden: impl Fn(&ClozeDeletion) -> String,
+) -> String {
+    let deletions = parse_deletions(text);
+    let Some(own) = deletions
+        .iter()
+        .find(|deletion| deletion.start == range.start && deletion.end == range.end)
+    else {
+        // a range that isn't one of the brackets is applied as given
+        let deletion = parse_deletion(text, range.start, range.end);
+        return format!(
+            "{}{}{}",
+            &text[..range.start],
+            hidden(&deletion),
+            &text[range.end..]
+        );
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for deletion in &deletions {
+        out.push_str(&text[last..deletion.start]);
+        if deletion.hidden_with(own) {
+            out.push_str(&hidden(deletion));
+        } else {
+            out.push_str(&deletion.shown_answer());
+        }
+        last = deletion.end;
+    }
+    out.push_str(&text[last..]);
+    out
 }
 
 #[cfg(test)]
@@ -66,4 +246,102 @@ mod tests {
             "Capital of 日本 is [______________________________]"
         );
     }
+
+    #[test]
+    fn skips_code_links_and_escapes() {
+        let deletions = |text: &str| -> Vec<String> {
+            find_cloze_ranges(text)
+                .into_iter()
+                .map(|(start, end)| text[start..end].to_string())
+                .collect()
+        };
+        assert_eq!(
+            deletions("See [docs](https://example.com) for [Tokyo]"),
+            vec!["[Tokyo]"]
+        );
+        assert_eq!(
+            deletions("`arr[0]` is the [first element]"),
+            vec!["[first element]"]
+        );
+        assert_eq!(deletions("\\[not this\\] but [this]"), vec!["[this]"]);
+        assert_eq!(
+            deletions("[outer [inner] text] and [x]"),
+            vec!["[outer [inner] text]", "[x]"]
+        );
+        assert_eq!(deletions("Region: [`us-east-2`]"), vec!["[`us-east-2`]"]);
+        assert_eq!(deletions("a [b and [c] d"), vec!["[c]"]);
+        assert_eq!(
+            deletions("an [open bracket\n\nthen [x] and [y]"),
+            vec!["[x]", "[y]"]
+        );
+        assert_eq!(deletions("[x] and a stray ] then [y]"), vec!["[x]", "[y]"]);
+
+        let fenced = "The [answer] is\n\n```rust\nlet v = arr[0];\n```\n";
+        assert_eq!(deletions(fenced), vec!["[answer]"]);
+    }
+
+    #[test]
+    fn groups_and_hints() {
+        let text =
+            "[c1: Tokyo] and [c1: Osaka] are in Japan; [Kyoto | old capital] too; [c2: Nara]";
+        let deletions = parse_deletions(text);
+        assert_eq!(deletions[0].group, Some(1));
+        assert_eq!(deletions[0].answer, "Tokyo");
+        assert_eq!(deletions[2].answer, "Kyoto");
+        assert_eq!(deletions[2].hint, Some("old capital"));
+        assert_eq!(parse_deletions("[|x|]")[0].answer, "|x|");
+
+        // a pipe without spaces around it, or escaped, is part of the answer
+        for (text, shown) in [
+            ("[a|b]", "a|b"),
+            ("[ls \\| grep foo]", "ls | grep foo"),
+            ("[c1: x \\| y | pipe]", "x | y"),
+        ] {
+            let deletion = &parse_deletions(text)[0];
+            assert_eq!(deletion.shown_answer(), shown);
+            let range = ClozeRange::new(deletion.start, deletion.end).unwrap();
+            assert_eq!(reveal_cloze_text(text, &range), format!("[{shown}]"));
+        }
+        assert_eq!(parse_deletions("[a|b]")[0].hint, None);
+        assert_eq!(
+            parse_deletions("[c1: x \\| y | pipe]")[0].hint,
+            Some("pipe")
+        );
+
+        let starts: Vec<&str> = cloze_card_starts(&deletions)
+            .iter()
+            .map(|deletion| deletion.answer)
+            .collect();
+        assert_eq!(starts, vec!["Tokyo", "Kyoto", "Nara"]);
+
+        let first = ClozeRange::new(deletions[0].start, deletions[0].end).unwrap();
+        assert_eq!(
+            mask_cloze_text(text, &first),
+            "[_____] and [_____] are in Japan; Kyoto too; Nara"
+        );
+        assert_eq!(
+            reveal_cloze_text(text, &first),
+            "[Tokyo] and [Osaka] are in Japan; Kyoto too; Nara"
+        );
+        let hinted = ClozeRange::new(deletions[2].start, deletions[2].end).unwrap();
+        assert_eq!(
+            mask_cloze_text(text, &hinted),
+            "Tokyo and Osaka are in Japan; [_____ | old capital] too; Nara"
+        );
+    }
+
+    #[test]
+    fn siblings_show_the_other_deletions() {
+        let text = "The [order] of a group is [the size of its set].";
+        let ranges = find_cloze_ranges(text);
+        let second = ClozeRange::new(ranges[1].0, ranges[1].1).unwrap();
+        assert_eq!(
+            mask_cloze_text(text, &second),
+            format!("The order of a group is [{}].", "_".repeat(19))
+        );
+        assert_eq!(
+            reveal_cloze_text(text, &second),
+            "The order of a group is [the size of its set]."
+        );
+    }
 }
@@ -0,0 +1,262 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::commands::suspend::{named_cards, queried_cards};
+use crate::crud::DB;
+use crate::palette::Palette;
+use crate::utils::{DayBoundary, ask_yn, pluralize};
+
+/// What to do with the cards matching a query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CardAction {
+    /// Back to new, with lapses, leech flag, and review log cleared.
+    Reset,
+    /// Back to new; the review log is kept.
+    Forget,
+    /// Due at the start of this study day.
+    SetDue(NaiveDate),
+}
+
+impl CardAction {
+    /// What the action does to `cards`, e.g. "reset 2 cards".
+    fn describe(&self, cards: &str) -> String {
+        match self {
+            CardAction::Reset => format!("reset {cards}, deleting their review history"),
+            CardAction::Forget => format!("send {cards} back to new"),
+            CardAction::SetDue(date) => format!("make {cards} due on {}", date.format("%Y-%m-%d")),
+        }
+    }
+}
+
+/// Reset and forget can't be undone, so they only take a card's `path:line` or its full
+/// hash or id. The matching cards are listed and nothing changes until the user agrees,
+/// unless `yes` is set; `dry_run` only lists them.
+pub async fn run(
+    db: &DB,
+    action: CardAction,
+    query: &str,
+    paths: Vec<PathBuf>,
+    day: DayBoundary,
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
+    let cards = match action {
+        CardAction::Reset | CardAction::Forget => named_cards(db, query, paths).await?,
+        CardAction::SetDue(_) => queried_cards(db, query, paths).await?,
+    };
+    if cards.is_empty() {
+        println!(
+            "{}",
+            Palette::paint(Palette::WARNING, format!("No cards match '{query}'."))
+        );
+        if matches!(action, CardAction::Reset | CardAction::Forget) {
+            println!(
+                "{}",
+                Palette::dim("Reset and forget need the card's path:line, or its full hash or id.")
+            );
+        }
+        return Ok(());
+    }
+
+    for card in &cards {
+        println!("  {}", Palette::dim(card.location()));
+    }
+    let description = action.describe(&pluralize("card", cards.len()));
+    if dry_run {
+        println!(
+            "{}",
+            Palette::paint(Palette::INFO, format!("Would {description}."))
+        );
+        return Ok(());
+    }
+    if !yes && !ask_yn(format!("This will {description}.")) {
+        println!("{}", Palette::dim("Nothing changed."));
+        return Ok(());
+    }
+
+    let (changed, verb) = match action {
+        CardAction::Reset => (db.forget_cards(&cards, true).await?, "Reset".to_string()),
+        CardAction::Forget => (db.forget_cards(&cards, false).await?, "Forgot".to_string()),
+        CardAction::SetDue(date) => {
+            let due = day.start_of(date);
+            (
+                db.set_due_date(&cards, due).await?,
+                format!("Due on {}:", date.format("%Y-%m-%d")),
+            )
+        }
+    };
+    println!(
+        "{} {}",
+        Palette::paint(Palette::SUCCESS, verb),
+        Palette::paint(Palette::INFO, pluralize("card", changed as usize))
+    );
+    Ok(())
+}
+
+/// Parse `today`, `tomorrow`, `+N` days, or a `YYYY-MM-DD` date into a study day.
+pub fn parse_due_day(value: &str, day: DayBoundary, now: DateTime<Utc>) -> Result<NaiveDate> {
+    let today = day.study_day(now);
+    let value = value.trim();
+    match value {
+        "today" => return Ok(today),
+        "tomorrow" => return Ok(today + Duration::days(1)),
+        _ => {}
+    }
+    if let Some(days) = value.strip_prefix('+') {
+        let days: i64 = days
+            .parse()
+            .with_context(|| format!("'{value}' should be +N days"))?;
+        return Ok(today + Duration::days(days));
+    }
+    let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") else {
+        bail!("'{value}' is not a date; use YYYY-MM-DD, today, tomorrow, or +N");
+    };
+    Ok(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::crud::cards::ReviewOptions;
+    use crate::fsrs::{Performance, ReviewStatus};
+    use crate::parser::register_all_cards;
+
+    #[test]
+    fn parses_due_days() {
+        let day = DayBoundary::default();
+        let now = Utc::now();
+        let today = day.study_day(now);
+        assert_eq!(parse_due_day("today", day, now).unwrap(), today);
+        assert_eq!(
+            parse_due_day("+3", day, now).unwrap(),
+            today + Duration::days(3)
+        );
+        assert_eq!(
+            parse_due_day("2026-01-31", day, now).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 1, 31).unwrap()
+        );
+        assert!(parse_due_day("soon", day, now).is_err());
+        assert!(parse_due_day("+x", day, now).is_err());
+    }
+
+    #[tokio::test]
+    async fn forget_reset_and_set_due() {
+        let db = DB::new_in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("deck.md"),
+            "Q: Capital of Japan?\nA: Tokyo\n\nQ: Capital of France?\nA: Paris",
+        )
+        .unwrap();
+        let paths = vec![dir.path().to_path_buf()];
+        let (card_hashes, _) = register_all_cards(&db, paths.clone()).await.unwrap();
+        let options = ReviewOptions::default();
+        for card in card_hashes.values() {
+            db.update_card_performance(
+                card,
+                ReviewStatus::Good,
+                None,
+                &options,
+                std::time::Duration::ZERO,
+            )
+            .await
+            .unwrap();
+        }
+        let tokyo = queried_cards(&db, "tokyo", paths.clone()).await.unwrap();
+        let paris = queried_cards(&db, "paris", paths.clone()).await.unwrap();
+
+        let day = DayBoundary::default();
+        run(
+            &db,
+            CardAction::Reset,
+            "tokyo",
+            paths.clone(),
+            day,
+            false,
+            true,
+        )
+        .await
+        .unwrap();
+        run(
+            &db,
+            CardAction::Forget,
+            "deck.md:1",
+            paths.clone(),
+            day,
+            true,
+            true,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            db.get_card_performance(&tokyo[0]).await.unwrap(),
+            Performance::Reviewed(_)
+        ));
+
+        run(
+            &db,
+            CardAction::Forget,
+            "deck.md:1",
+            paths.clone(),
+            day,
+            false,
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            db.get_card_performance(&tokyo[0]).await.unwrap(),
+            Performance::New
+        );
+        assert_eq!(
+            db.review_log_for_card(&tokyo[0].card_hash)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(matches!(
+            db.get_card_performance(&paris[0]).await.unwrap(),
+            Performance::Reviewed(_)
+        ));
+
+        run(
+            &db,
+            CardAction::Reset,
+            &tokyo[0].card_hash,
+            paths.clone(),
+            day,
+            false,
+            true,
+        )
+        .await
+        .unwrap();
+        assert!(
+            db.review_log_for_card(&tokyo[0].card_hash)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let date = NaiveDate::from_ymd_opt(2030, 5, 1).unwrap();
+        run(
+            &db,
+            CardAction::SetDue(date),
+            "paris",
+            paths,
+            day,
+            false,
+            true,
+        )
+        .await
+        .unwrap();
+        let Performance::Reviewed(paris) = db.get_card_performance(&paris[0]).await.unwrap() else {
+            panic!("expected a reviewed card");
+        };
+        assert_eq!(paris.due_date, DayBoundary::default().start_of(date));
+    }
+}
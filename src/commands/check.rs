@@ -1,12 +1,15 @@
 use crate::{
     check_version::{check_version, prompt_for_new_version},
+    commands::simulate::simulate_cards,
+    config::Config,
     crud::DB,
     notes::register_apple_notes_cards,
     palette::Palette,
-    parser::{FileSearchStats, register_all_cards},
+    parser::{FileSearchStats, TagFilter, register_all_cards},
+    simulation::{SimulationOptions, WorkloadSimulation},
     stats::{CardLifeCycle, CardStats, Histogram},
     tui::Theme,
-    utils::pluralize,
+    utils::{DayBoundary, pluralize},
 };
 
 use std::{
@@ -31,16 +34,47 @@ use ratatui::{
     widgets::{Bar, BarChart, BarGroup, Paragraph, Wrap},
 };
 
-pub async fn run(db: &DB, paths: Vec<PathBuf>, plain: bool, apple_notes: bool) -> Result<usize> {
+pub async fn run(
+    db: &DB,
+    paths: Vec<PathBuf>,
+    plain: bool,
+    apple_notes: bool,
+    tags: &TagFilter,
+    workload: bool,
+) -> Result<usize> {
     let version_check = tokio::spawn(check_version(db.clone()));
 
-    let (card_hashes, file_traversal_stats) = if apple_notes {
+    let config = Config::load_for(&paths)?;
+    let (mut card_hashes, file_traversal_stats) = if apple_notes {
         register_apple_notes_cards(db).await?
     } else {
         register_all_cards(db, paths).await?
     };
+    card_hashes.retain(|_, card| tags.matches(card));
     let count = card_hashes.len();
-    let crud_stats = db.collection_stats(&card_hashes).await?;
+    let mut crud_stats = db.collection_stats(&card_hashes, config.day).await?;
+    crud_stats.daily_limits = config.daily_limits;
+    // simulating takes a while on large collections, so the chart is opt-in
+    let simulation = if workload && !plain {
+        let options = SimulationOptions {
+            steps: config.steps,
+            intervals: config.intervals,
+            ..Default::default()
+        };
+        match simulate_cards(db, &card_hashes, &options).await {
+            Ok(simulation) => simulation,
+            Err(err) => {
+                println!(
+                    "{} {}",
+                    Palette::paint(Palette::WARNING, "Couldn't simulate the workload:"),
+                    Palette::dim(format!("{err:#}"))
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
     if let Some(notification) = version_check.await.ok().flatten() {
         prompt_for_new_version(db, &notification).await;
     }
@@ -48,12 +82,16 @@ pub async fn run(db: &DB, paths: Vec<PathBuf>, plain: bool, apple_notes: bool) -
     if plain {
         render_plain_summary(&crud_stats, &file_traversal_stats);
     } else {
-        render_dashboard(&crud_stats, &file_traversal_stats)?;
+        render_dashboard(&crud_stats, &file_traversal_stats, simulation.as_ref())?;
     }
     Ok(count)
 }
 
-fn render_dashboard(crud_stats: &CardStats, file_traversal_stats: &FileSearchStats) -> Result<()> {
+fn render_dashboard(
+    crud_stats: &CardStats,
+    file_traversal_stats: &FileSearchStats,
+    simulation: Option<&WorkloadSimulation>,
+) -> Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
@@ -61,7 +99,7 @@ fn render_dashboard(crud_stats: &CardStats, file_traversal_stats: &FileSearchSta
     let mut terminal = Terminal::new(backend)?;
     terminal.hide_cursor()?;
 
-    let draw_result = dashboard_loop(&mut terminal, crud_stats, file_traversal_stats);
+    let draw_result = dashboard_loop(&mut terminal, crud_stats, file_traversal_stats, simulation);
 
     terminal.show_cursor()?;
     disable_raw_mode()?;
@@ -124,6 +162,13 @@ fn render_plain_summary(crud_stats: &CardStats, file_traversal_stats: &FileSearc
         Palette::dim("Total cards indexed in DB:"),
         Palette::paint(Palette::INFO, crud_stats.total_cards_in_db)
     );
+    println!(
+        "{} {} {} {}",
+        Palette::dim("Suspended:"),
+        Palette::paint(Palette::INFO, crud_stats.suspended_cards),
+        Palette::dim("Buried:"),
+        Palette::paint(Palette::INFO, crud_stats.buried_cards)
+    );
 
     println!("\n{}", Palette::paint(Palette::ACCENT, "Due Status"));
     let load_factor = if crud_stats.num_cards == 0 {
@@ -147,6 +192,25 @@ fn render_plain_summary(crud_stats: &CardStats, file_traversal_stats: &FileSearc
         Palette::dim("Due now:"),
         Palette::paint(due_color, crud_stats.due_cards)
     );
+    println!(
+        "{} {} {} {}",
+        Palette::dim("New today:"),
+        Palette::paint(
+            Palette::INFO,
+            format_daily_count(
+                crud_stats.studied_today.new_cards,
+                crud_stats.daily_limits.new_cards
+            )
+        ),
+        Palette::dim("Reviews today:"),
+        Palette::paint(
+            Palette::INFO,
+            format_daily_count(
+                crud_stats.studied_today.reviews,
+                crud_stats.daily_limits.reviews
+            )
+        )
+    );
     println!(
         "{} {}",
         Palette::dim("Next 7 days:"),
@@ -163,7 +227,7 @@ fn render_plain_summary(crud_stats: &CardStats, file_traversal_stats: &FileSearc
         println!(
             "{} {}",
             Palette::dim("Next due:"),
-            Palette::paint(Palette::INFO, format_next_due(next_due))
+            Palette::paint(Palette::INFO, format_next_due(next_due, crud_stats.day))
         );
     }
 
@@ -268,17 +332,25 @@ fn format_bar(count: usize, max: usize) -> String {
     )
 }
 
-fn format_next_due(due_date: &DateTime<Utc>) -> String {
+/// `done/limit (n left)`, or just `done` without a daily cap.
+fn format_daily_count(done: usize, limit: Option<usize>) -> String {
+    match limit {
+        Some(limit) => format!("{done}/{limit} ({} left)", limit.saturating_sub(done)),
+        None => done.to_string(),
+    }
+}
+
+fn format_next_due(due_date: &DateTime<Utc>, day: DayBoundary) -> String {
     let local_time = due_date.with_timezone(&chrono::Local);
     let now = Utc::now();
     let duration = due_date.signed_duration_since(now);
 
-    let relative = if duration.num_days() >= 1 {
-        format!("in {} days", duration.num_days())
-    } else if duration.num_hours() >= 1 {
-        format!("in {} hours", duration.num_hours())
-    } else {
-        format!("in {} min", duration.num_minutes().max(1))
+    // whole days count study days, so "tomorrow" means after the next rollover
+    let relative = match day.days_until(now, *due_date) {
+        days if days > 1 => format!("in {days} days"),
+        1 => "tomorrow".to_string(),
+        _ if duration.num_hours() >= 1 => format!("in {} hours", duration.num_hours()),
+        _ => format!("in {} min", duration.num_minutes().max(1)),
     };
 
     format!("{} ({})", local_time.format("%Y-%m-%d %H:%M"), relative)
@@ -288,9 +360,11 @@ fn dashboard_loop(
     terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
     crud_stats: &CardStats,
     file_traversal_stats: &FileSearchStats,
+    simulation: Option<&WorkloadSimulation>,
 ) -> Result<()> {
     loop {
-        terminal.draw(|frame| draw_dashboard(frame, crud_stats, file_traversal_stats))?;
+        terminal
+            .draw(|frame| draw_dashboard(frame, crud_stats, file_traversal_stats, simulation))?;
 
         if event::poll(Duration::from_millis(200))?
             && let Event::Key(key) = event::read()?
@@ -312,15 +386,18 @@ fn draw_dashboard(
     frame: &mut Frame<'_>,
     crud_stats: &CardStats,
     file_traversal_stats: &FileSearchStats,
+    simulation: Option<&WorkloadSimulation>,
 ) {
     let area = frame.area();
     frame.render_widget(Theme::backdrop(), area);
 
+    let chart_height = if simulation.is_some() { 9 } else { 0 };
     let rows = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(8),
             Constraint::Min(6),
+            Constraint::Length(chart_height),
             Constraint::Length(3),
         ])
         .split(area);
@@ -345,7 +422,11 @@ fn draw_dashboard(
 
     render_fsrs_panel(frame, mid[1], crud_stats);
 
-    frame.render_widget(help_panel(crud_stats), rows[2]);
+    if let Some(simulation) = simulation {
+        render_retention_chart(frame, rows[2], simulation);
+    }
+
+    frame.render_widget(help_panel(crud_stats), rows[3]);
 }
 
 fn collection_panel(
@@ -407,6 +488,15 @@ fn collection_panel(
             Theme::bullet(),
             Theme::label_span(format!("{}", crud_stats.total_cards_in_db)),
         ]),
+        Line::from(vec![
+            Theme::span("Suspended"),
+            Theme::bullet(),
+            Theme::label_span(format!("{}", crud_stats.suspended_cards)),
+            Theme::bullet(),
+            Theme::span("Buried"),
+            Theme::bullet(),
+            Theme::label_span(format!("{}", crud_stats.buried_cards)),
+        ]),
     ];
     Paragraph::new(lines).block(Theme::panel("Collection"))
 }
@@ -437,6 +527,21 @@ fn due_panel(stats: &CardStats) -> Paragraph<'static> {
             Theme::label_span(format!("{}", stats.due_cards)),
             Theme::span("  "),
         ]),
+        Line::from(vec![
+            Theme::span("New today"),
+            Theme::bullet(),
+            Theme::label_span(format_daily_count(
+                stats.studied_today.new_cards,
+                stats.daily_limits.new_cards,
+            )),
+            Theme::bullet(),
+            Theme::span("Reviews today"),
+            Theme::bullet(),
+            Theme::label_span(format_daily_count(
+                stats.studied_today.reviews,
+                stats.daily_limits.reviews,
+            )),
+        ]),
         Line::from(vec![
             Theme::span("Next 7 days"),
             Theme::bullet(),
@@ -453,7 +558,7 @@ fn due_panel(stats: &CardStats) -> Paragraph<'static> {
         lines.push(Line::from(vec![
             Theme::span("Next due"),
             Theme::bullet(),
-            Theme::label_span(format_next_due(next_due)),
+            Theme::label_span(format_next_due(next_due, stats.day)),
         ]));
     }
     Paragraph::new(lines).block(Theme::panel("Due Status"))
@@ -626,6 +731,65 @@ fn render_fsrs_panel(frame: &mut Frame<'_>, area: Rect, stats: &CardStats) {
     );
 }
 
+fn render_retention_chart(frame: &mut Frame<'_>, area: Rect, simulation: &WorkloadSimulation) {
+    let block = Theme::panel_with_line(Theme::title_line("Retention vs Workload"));
+    frame.render_widget(block.clone(), area);
+    let inner = block.inner(area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(3)])
+        .split(inner);
+
+    let best = simulation.recommended_outcome();
+    let header = Paragraph::new(Line::from(vec![
+        Theme::span("Minutes per day over the next year"),
+        Theme::bullet(),
+        Theme::span("Recommended"),
+        Theme::bullet(),
+        Span::styled(format!("{:.0}%", best.retention * 100.0), Theme::success()),
+        Theme::span(format!(
+            " (~{:.1} min/day, {:.0} remembered)",
+            best.minutes_per_day, best.memorized
+        )),
+    ]));
+    frame.render_widget(header, chunks[0]);
+
+    let bars: Vec<Bar<'static>> = simulation
+        .outcomes
+        .iter()
+        .enumerate()
+        .map(|(idx, outcome)| {
+            let style = if idx == simulation.recommended {
+                Theme::success()
+            } else {
+                Theme::label()
+            };
+            Bar::default()
+                .value((outcome.minutes_per_day * 10.0).round() as u64)
+                .text_value(format!("{:.1}m", outcome.minutes_per_day))
+                .label(Line::from(vec![Theme::span(format!(
+                    "{:.0}%",
+                    outcome.retention * 100.0
+                ))]))
+                .style(style)
+        })
+        .collect();
+
+    let len = cmp::max(bars.len() as u16, 1);
+    let mut chart_area = chunks[1];
+    let right_pad = cmp::min(2, chart_area.width);
+    chart_area.width = chart_area.width.saturating_sub(right_pad);
+    let bar_width = cmp::max(1, chart_area.width.saturating_sub(len) / len);
+
+    let chart = BarChart::default()
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(bar_width)
+        .bar_style(Theme::label())
+        .bar_gap(1);
+
+    frame.render_widget(chart, chart_area);
+}
+
 fn help_panel(stats: &CardStats) -> Paragraph<'static> {
     let lines = vec![
         Line::from(vec![
@@ -653,7 +817,14 @@ mod tests {
     use crate::parser::FileSearchStats;
     use crate::stats::CardStats;
 
-    use super::{format_upcoming_label, render_plain_summary};
+    use super::{format_daily_count, format_upcoming_label, render_plain_summary};
+
+    #[test]
+    fn format_daily_count_shows_what_is_left() {
+        assert_eq!(format_daily_count(3, Some(20)), "3/20 (17 left)");
+        assert_eq!(format_daily_count(25, Some(20)), "25/20 (0 left)");
+        assert_eq!(format_daily_count(7, None), "7");
+    }
 
     #[test]
     fn format_upcoming_label_pretty_prints_dates() {
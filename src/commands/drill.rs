@@ -1,21 +1,29 @@
+use std::collections::{HashMap, HashSet};
 use std::io;
 use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
 use crate::card::{Card, CardContent};
-use crate::cloze_utils::mask_cloze_text;
+use crate::cloze_utils::{mask_cloze_text, reveal_cloze_text};
 use crate::crud::DB;
-use crate::fsrs::{LEARN_AHEAD_THRESHOLD_MINS, ReviewStatus};
+use crate::crud::cards::{ReviewOptions, ReviewUndo};
+use crate::crud::limits::{DailyLimits, SessionLimits};
+use crate::fsrs::{
+    LEARN_AHEAD_THRESHOLD_MINS, ReviewStatus, decay, preview_intervals, validate_retention,
+};
+use crate::fuzz::DueLoad;
 use crate::llm::drill_preprocessor::{AIStatus, DrillPreprocessor};
 use crate::notes::register_apple_notes_cards;
+use crate::order::{DrillOrder, OrderContext, order_cards};
 use crate::palette::Palette;
-use crate::parser::register_all_cards;
 use crate::parser::render_markdown;
 use crate::parser::{Media, extract_media};
+use crate::parser::{TagFilter, register_all_cards};
 use crate::tui::Theme;
-use crate::utils::pluralize;
+use crate::utils::{DayBoundary, pluralize};
 
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
 use crossterm::event::KeyModifiers;
 use crossterm::{
     event::{
@@ -29,6 +37,7 @@ use ratatui::{
     Terminal,
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout},
+    style::Style,
     text::{Line, Span},
     widgets::{Paragraph, Wrap},
 };
@@ -42,145 +51,496 @@ pub struct DrillOptions {
     pub card_limit: Option<usize>,
     pub new_card_limit: Option<usize>,
     pub rephrase_questions: bool,
-    pub shuffle: bool,
-    pub retention: f32,
+    pub order: DrillOrder,
+    /// Reviews between new cards with [`DrillOrder::InterleaveNew`].
+    pub new_card_spacing: usize,
+    pub review: ReviewOptions,
+    pub day: DayBoundary,
+    pub daily_limits: DailyLimits,
+    /// Steer fuzzed intervals towards days with fewer cards due.
+    pub load_balance: bool,
+    /// Only drill cards matching this text, path:line, or hash prefix.
+    pub filter: Option<String>,
+    /// Only drill cards with these tags.
+    pub tags: TagFilter,
+    pub mode: DrillMode,
     pub apple_notes: bool,
 }
 
-pub async fn run(db: &DB, opts: DrillOptions) -> Result<()> {
-    validate_retention(opts.retention)?;
-    let (hash_cards, _) = if opts.apple_notes {
+/// Whether grades schedule the cards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DrillMode {
+    #[default]
+    Schedule,
+    /// Go through every card regardless of when it is due, without scheduling anything.
+    /// With `log`, grades are still written to the review log, marked as cram reviews.
+    Cram { log: bool },
+}
+
+pub async fn run(db: &DB, mut opts: DrillOptions) -> Result<()> {
+    validate_retention(opts.review.retention)?;
+    let (mut hash_cards, _) = if opts.apple_notes {
         register_apple_notes_cards(db).await?
     } else {
         register_all_cards(db, opts.paths).await?
     };
-    let mut cards_due_today = db
-        .due_today(&hash_cards, opts.card_limit, opts.new_card_limit)
-        .await?;
+    if let Some(filter) = &opts.filter {
+        hash_cards.retain(|_, card| card.matches_query(filter));
+    }
+    hash_cards.retain(|_, card| opts.tags.matches(card));
+    let mut remaining = DailyLimits::default();
+    let mut cards_due_today = if opts.mode == DrillMode::Schedule {
+        let studied = db
+            .studied_since(opts.day.start_of_today(chrono::Utc::now()))
+            .await?;
+        remaining = opts.daily_limits.remaining(&studied);
+        let limits = SessionLimits {
+            cards: opts.card_limit,
+            new_cards: min_limit(opts.new_card_limit, remaining.new_cards),
+            reviews: remaining.reviews,
+        };
+        db.due_today(&hash_cards, limits, opts.day).await?
+    } else {
+        let mut cards: Vec<Card> = hash_cards.values().cloned().collect();
+        cards.sort_by(|a, b| {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.file_card_range.cmp(&b.file_card_range))
+        });
+        cards
+    };
+    if opts.load_balance && opts.mode == DrillMode::Schedule {
+        let stats = db.collection_stats(&hash_cards, opts.day).await?;
+        opts.review.load = Some(DueLoad::from_upcoming_week(
+            &stats.upcoming_week,
+            opts.day,
+            Utc::now(),
+        ));
+    }
 
-    if opts.shuffle {
-        use rand::seq::SliceRandom;
-        cards_due_today.shuffle(&mut rand::rng());
+    let rows = match opts.order {
+        DrillOrder::Retrievability | DrillOrder::InterleaveNew | DrillOrder::NewLast => db
+            .card_stats_rows(&hash_cards)
+            .await?
+            .into_iter()
+            .map(|row| (row.card_hash.clone(), row))
+            .collect(),
+        _ => HashMap::new(),
+    };
+    order_cards(
+        &mut cards_due_today,
+        opts.order,
+        &OrderContext {
+            rows: &rows,
+            decay: decay(&db.fsrs_parameters().await?),
+            now: Utc::now(),
+            new_card_spacing: opts.new_card_spacing,
+        },
+    );
+    if let (DrillMode::Cram { .. }, Some(limit)) = (opts.mode, opts.card_limit) {
+        cards_due_today.truncate(limit);
     }
 
+    if cards_due_today.is_empty() && opts.mode != DrillMode::Schedule {
+        println!("{}", Palette::paint(Palette::WARNING, "No cards to cram."));
+        return Ok(());
+    }
     if cards_due_today.is_empty() {
         println!(
             "{}",
             Palette::paint(Palette::SUCCESS, "All caught up—no cards due today.")
         );
+        if remaining.new_cards == Some(0) || remaining.reviews == Some(0) {
+            println!(
+                "{}",
+                Palette::dim("Today's limits are used up; more cards unlock tomorrow.")
+            );
+        }
         return Ok(());
     }
 
     let drill_preprocessor =
         DrillPreprocessor::new(&cards_due_today, opts.rephrase_questions).await?;
     drill_preprocessor.initialize_card_status(&mut cards_due_today);
-    start_drill_session(db, cards_due_today, drill_preprocessor, opts.retention).await?;
+    start_drill_session(
+        db,
+        cards_due_today,
+        drill_preprocessor,
+        opts.review,
+        opts.day,
+        opts.mode,
+    )
+    .await?;
 
     Ok(())
 }
 
-fn validate_retention(retention: f32) -> Result<()> {
-    if retention > 1.0 {
-        bail!("Retention must be less than or equal to 1.0")
+fn min_limit(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
     }
-    if retention < 0.65 {
-        bail!("Retention must be greater than 0.65")
-    }
-    Ok(())
 }
+
 struct DrillState<'a> {
     db: &'a DB,
+    /// Cards in the order they are shown; learning cards are slotted in once their step passes.
     cards: Vec<Card>,
-    redo_cards: Vec<Card>,
+    /// Cards waiting on a learning step later in this session, soonest first.
+    learning: Vec<LearningCard>,
     current_idx: usize,
+    show_hint: bool,
     show_answer: bool,
     last_action: Option<LastAction>,
     current_medias: Vec<Media>,
-    retention: f32,
+    /// Interval in days and study days until due under each grade.
+    interval_previews: Vec<(ReviewStatus, f64, i64)>,
+    card_shown_at: Instant,
+    review: ReviewOptions,
+    day: DayBoundary,
+    leeches: HashSet<String>,
+    history: Vec<ReviewedCard>,
+    mode: DrillMode,
+    /// Grades given in a cram session, in order.
+    crammed: Vec<CrammedCard>,
+}
+/// A review that can be taken back, with the queue as it was before it.
+struct ReviewedCard {
+    card: Card,
+    undo: ReviewUndo,
+    due: DateTime<Utc>,
+    cards: Vec<Card>,
+    learning: Vec<LearningCard>,
+    current_idx: usize,
+}
+struct CrammedCard {
+    card: Card,
+    grade: ReviewStatus,
+    revlog_id: Option<i64>,
+}
+#[derive(Clone)]
+struct LearningCard {
+    due: DateTime<Utc>,
+    card: Card,
 }
 struct LastAction {
     action: ReviewStatus,
-    show_again_duration: f64,
+    /// `None` in cram sessions, where nothing is scheduled.
+    show_again_duration: Option<f64>,
+    /// Study days until the card is due again.
+    show_again_days: i64,
     last_reviewed_at: Instant,
 }
 impl LastAction {
     fn print(&self) -> String {
+        let Some(show_again_duration) = self.show_again_duration else {
+            return format!(" {}", self.action.label());
+        };
         let mut show_again = String::new();
-        if self.show_again_duration <= 15.0 / MINUTES_PER_DAY {
+        if show_again_duration <= 15.0 / MINUTES_PER_DAY {
             show_again.push_str("<15 mins");
-        } else if self.show_again_duration <= 30.0 / MINUTES_PER_DAY {
+        } else if show_again_duration <= 30.0 / MINUTES_PER_DAY {
             show_again.push_str("<30 mins");
-        } else if self.show_again_duration <= 0.5 {
+        } else if show_again_duration <= 0.5 {
             show_again.push_str("<12 hours");
-        } else if self.show_again_duration <= 1.0 {
+        } else if show_again_duration < 1.0 {
             show_again.push_str("<1 day");
         } else {
-            show_again.push_str(format!("{} days", self.show_again_duration as i64).as_str());
+            // whole study days, so a card due after the next rollover is "1 day" away
+            show_again.push_str(&pluralize("day", self.show_again_days.max(1) as usize));
         }
         format!(" {} (See again in {})", self.action.label(), show_again,)
     }
 }
 
 impl<'a> DrillState<'a> {
-    fn new(db: &'a DB, cards: Vec<Card>, retention: f32) -> Self {
+    fn new(db: &'a DB, cards: Vec<Card>, review: ReviewOptions, day: DayBoundary) -> Self {
         Self {
             db,
             cards,
-            redo_cards: Vec::new(),
+            learning: Vec::new(),
             current_idx: 0,
+            show_hint: false,
             show_answer: false,
             last_action: None,
             current_medias: Vec::new(),
-            retention,
+            interval_previews: Vec::new(),
+            card_shown_at: Instant::now(),
+            review,
+            day,
+            leeches: HashSet::new(),
+            history: Vec::new(),
+            mode: DrillMode::Schedule,
+            crammed: Vec::new(),
         }
     }
 
-    fn current_card(&mut self) -> Option<Card> {
-        if self.current_idx >= self.cards.len() {
-            if self.redo_cards.is_empty() {
-                return None;
-            }
-            self.cards = std::mem::take(&mut self.redo_cards);
-            self.current_idx = 0;
-        }
+    /// The card on screen, or `None` while waiting for a learning step to pass.
+    fn current_card(&self) -> Option<Card> {
         self.cards.get(self.current_idx).cloned()
     }
 
-    fn reveal_answer(&mut self) {
+    /// When the next learning card can be shown, if the session is waiting on one.
+    fn waiting_until(&self) -> Option<DateTime<Utc>> {
+        if self.current_idx < self.cards.len() {
+            return None;
+        }
+        self.learning.first().map(|learning| learning.due)
+    }
+
+    /// Slot the soonest learning card in as the next card once its step has passed.
+    /// Only call this when the next card is not on screen yet.
+    fn promote_due_learning(&mut self, now: DateTime<Utc>) -> bool {
+        if self
+            .learning
+            .first()
+            .is_none_or(|learning| learning.due > now)
+        {
+            return false;
+        }
+        let learning = self.learning.remove(0);
+        self.cards.insert(self.current_idx, learning.card);
+        true
+    }
+
+    fn schedule_learning(&mut self, card: Card, due: DateTime<Utc>) {
+        let idx = self
+            .learning
+            .partition_point(|learning| learning.due <= due);
+        self.learning.insert(idx, LearningCard { due, card });
+    }
+
+    fn advance(&mut self) {
+        self.current_idx += 1;
+        self.promote_due_learning(Utc::now());
+        self.show_hint = false;
+        self.show_answer = false;
+        self.interval_previews.clear();
+        self.card_shown_at = Instant::now();
+    }
+
+    async fn reveal_answer(&mut self) -> Result<()> {
+        let current_card = self
+            .current_card()
+            .expect("card should exist when revealing answer");
+        if self.mode != DrillMode::Schedule {
+            self.show_answer = true;
+            return Ok(());
+        }
+        let performance = self.db.get_card_performance(&current_card).await?;
+        let parameters = self.db.fsrs_parameters().await?;
+        let now = Utc::now();
+        self.interval_previews = preview_intervals(
+            performance,
+            now,
+            self.review.retention,
+            &parameters,
+            &self.review.steps,
+            &self.review.intervals,
+        )?
+        .into_iter()
+        .map(|(status, interval)| {
+            let due = now + chrono::Duration::seconds((interval * MINUTES_PER_DAY * 60.0) as i64);
+            (status, interval, self.day.days_until(now, due))
+        })
+        .collect();
         self.show_answer = true;
+        Ok(())
     }
 
     async fn handle_review(&mut self, action: ReviewStatus) -> Result<()> {
         let current_card = self
             .current_card()
             .expect("card should exist when handling review");
-        let show_again_duration = self
+        if let DrillMode::Cram { log } = self.mode {
+            return self.handle_cram(current_card, action, log).await;
+        }
+        let outcome = self
             .db
-            .update_card_performance(&current_card, action, None, self.retention)
+            .update_card_performance(
+                &current_card,
+                action,
+                None,
+                &self.review,
+                self.card_shown_at.elapsed(),
+            )
             .await?;
-        if action == ReviewStatus::Fail
-            || show_again_duration
-                < (LEARN_AHEAD_THRESHOLD_MINS.num_minutes() as f64 / MINUTES_PER_DAY)
-        {
-            self.redo_cards.push(current_card.clone());
+        let show_again_duration = outcome.interval_raw;
+        if outcome.leech {
+            self.leeches.insert(current_card.card_hash.clone());
+        }
+        if let Some(load) = &mut self.review.load {
+            load.add(outcome.due_date);
+        }
+        self.history.push(ReviewedCard {
+            card: current_card.clone(),
+            undo: outcome.undo,
+            due: outcome.due_date,
+            cards: self.cards.clone(),
+            learning: self.learning.clone(),
+            current_idx: self.current_idx,
+        });
+        // cards on a short step come back later this session, once the step has passed
+        if !outcome.auto_suspended && outcome.due_date <= Utc::now() + LEARN_AHEAD_THRESHOLD_MINS {
+            self.schedule_learning(current_card, outcome.due_date);
         }
 
         self.last_action = Some(LastAction {
             action,
-            show_again_duration,
+            show_again_duration: Some(show_again_duration),
+            show_again_days: self.day.days_until(Utc::now(), outcome.due_date),
             last_reviewed_at: std::time::Instant::now(),
         });
-        self.current_idx += 1;
+        self.advance();
+        Ok(())
+    }
+
+    /// Record a cram grade; the card's scheduling is never touched.
+    async fn handle_cram(&mut self, card: Card, action: ReviewStatus, log: bool) -> Result<()> {
+        let revlog_id = if log {
+            Some(
+                self.db
+                    .log_cram_review(
+                        &card.card_hash,
+                        action,
+                        Utc::now(),
+                        self.card_shown_at.elapsed(),
+                    )
+                    .await?,
+            )
+        } else {
+            None
+        };
+        self.crammed.push(CrammedCard {
+            card,
+            grade: action,
+            revlog_id,
+        });
+        self.last_action = Some(LastAction {
+            action,
+            show_again_duration: None,
+            show_again_days: 0,
+            last_reviewed_at: std::time::Instant::now(),
+        });
+        self.advance();
+        Ok(())
+    }
+
+    /// Take back the most recent review and return to that card. Returns false when there
+    /// is nothing left to undo.
+    async fn undo_last(&mut self) -> Result<bool> {
+        if self.mode != DrillMode::Schedule {
+            return self.undo_cram().await;
+        }
+        let Some(reviewed) = self.history.pop() else {
+            return Ok(false);
+        };
+        self.db.undo_review(&reviewed.card, &reviewed.undo).await?;
+        if let Some(load) = &mut self.review.load {
+            load.remove(reviewed.due);
+        }
+        if !reviewed.undo.leech {
+            self.leeches.remove(&reviewed.card.card_hash);
+        }
+        self.cards = reviewed.cards;
+        self.learning = reviewed.learning;
+        self.current_idx = reviewed.current_idx;
+        self.last_action = None;
+        self.show_hint = false;
         self.show_answer = false;
+        self.interval_previews.clear();
+        self.card_shown_at = Instant::now();
+        Ok(true)
+    }
+
+    async fn undo_cram(&mut self) -> Result<bool> {
+        let Some(crammed) = self.crammed.pop() else {
+            return Ok(false);
+        };
+        if let Some(id) = crammed.revlog_id {
+            self.db.delete_cram_review(id).await?;
+        }
+        // cram sessions never reorder cards, so the graded card is the one before
+        self.current_idx = self.current_idx.saturating_sub(1);
+        self.last_action = None;
+        self.show_hint = false;
+        self.show_answer = false;
+        self.card_shown_at = Instant::now();
+        Ok(true)
+    }
+
+    fn can_undo(&self) -> bool {
+        match self.mode {
+            DrillMode::Schedule => !self.history.is_empty(),
+            DrillMode::Cram { .. } => !self.crammed.is_empty(),
+        }
+    }
+
+    /// Keep the current card out of drills until tomorrow.
+    async fn bury_current(&mut self) -> Result<()> {
+        let current_card = self.current_card().expect("card should exist when burying");
+        self.db
+            .bury_card(
+                &current_card,
+                self.day.start_of_next_day(chrono::Utc::now()),
+            )
+            .await?;
+        self.skip_card(&current_card);
+        Ok(())
+    }
+
+    /// Keep the current card out of drills until it is unsuspended.
+    async fn suspend_current(&mut self) -> Result<()> {
+        let current_card = self
+            .current_card()
+            .expect("card should exist when suspending");
+        self.db
+            .suspend_cards(std::slice::from_ref(&current_card))
+            .await?;
+        self.skip_card(&current_card);
         Ok(())
     }
 
+    /// Take `card` out of the session, and out of the queues saved for undo so that
+    /// undoing an earlier review doesn't bring it back. Its own reviews are no longer
+    /// undoable, since the card has left the session.
+    fn skip_card(&mut self, card: &Card) {
+        let skipped = |other: &Card| other.card_hash == card.card_hash;
+        self.history.retain(|reviewed| !skipped(&reviewed.card));
+        for reviewed in &mut self.history {
+            let earlier = reviewed.cards[..reviewed.current_idx]
+                .iter()
+                .filter(|other| skipped(other))
+                .count();
+            reviewed.cards.retain(|other| !skipped(other));
+            reviewed.current_idx -= earlier;
+            reviewed
+                .learning
+                .retain(|learning| !skipped(&learning.card));
+        }
+        self.learning.retain(|learning| !skipped(&learning.card));
+        self.advance();
+    }
+
     fn is_complete(&self) -> bool {
-        self.current_idx >= self.cards.len() && self.redo_cards.is_empty()
+        self.current_idx >= self.cards.len() && self.learning.is_empty()
     }
 
     fn apply_ai_update(&mut self, update: AiUpdate) {
-        for card in self.cards.iter_mut().chain(self.redo_cards.iter_mut()) {
+        let saved = self.history.iter_mut().flat_map(|reviewed| {
+            reviewed.cards.iter_mut().chain(
+                reviewed
+                    .learning
+                    .iter_mut()
+                    .map(|learning| &mut learning.card),
+            )
+        });
+        for card in self
+            .cards
+            .iter_mut()
+            .chain(self.learning.iter_mut().map(|learning| &mut learning.card))
+            .chain(saved)
+        {
             if card.card_hash == update.card_hash {
                 *card = update.card.clone();
                 card.ai_status = AIStatus::AiEnhanced;
@@ -208,8 +568,11 @@ async fn start_drill_session(
     db: &DB,
     cards: Vec<Card>,
     drill_preprocessor: DrillPreprocessor,
-    retention: f32,
+    review: ReviewOptions,
+    day: DayBoundary,
+    mode: DrillMode,
 ) -> Result<()> {
+    let leeches = db.leech_card_hashes().await?;
     enable_raw_mode().context("failed to enable raw mode")?;
     let mut stdout = io::stdout();
     execute!(
@@ -235,11 +598,14 @@ async fn start_drill_session(
         None
     };
 
-    let mut state = DrillState::new(db, cards, retention);
+    let mut state = DrillState::new(db, cards, review, day);
+    state.leeches = leeches;
+    state.mode = mode;
 
     let loop_result: Result<()> = async {
         loop {
-            if state.is_complete() {
+            // a finished session stays open while its last grade can still be taken back
+            if state.is_complete() && !state.can_undo() {
                 break Ok(());
             }
 
@@ -259,11 +625,12 @@ async fn start_drill_session(
                 ai_preprocess_handle = None;
             }
 
+            if state.waiting_until().is_some() && state.promote_due_learning(Utc::now()) {
+                state.card_shown_at = Instant::now();
+            }
+
             terminal
                 .draw(|frame| {
-                    let card = state
-                        .current_card()
-                        .expect("card should exist while session is active");
                     let area = frame.area();
                     frame.render_widget(Theme::backdrop(), area);
                     let chunks = Layout::default()
@@ -271,6 +638,21 @@ async fn start_drill_session(
                         .constraints([Constraint::Min(5), Constraint::Length(5)])
                         .split(area);
 
+                    let footer = Paragraph::new(instructions_text(&state))
+                        .block(Theme::panel_with_line(Theme::section_header("Controls")));
+                    frame.render_widget(footer, chunks[1]);
+
+                    let Some(card) = state.current_card() else {
+                        state.current_medias.clear();
+                        let panel = if state.is_complete() {
+                            finished_panel(&state)
+                        } else {
+                            waiting_panel(&state)
+                        };
+                        frame.render_widget(panel, chunks[0]);
+                        return;
+                    };
+
                     let mut header_vec = vec![
                         Theme::label_span(format!(
                             "Card {}/{}",
@@ -278,7 +660,7 @@ async fn start_drill_session(
                             state.cards.len()
                         )),
                         Theme::bullet(),
-                        Theme::span(format!("{} coming again", state.redo_cards.len())),
+                        Theme::span(format!("{} coming again", state.learning.len())),
                         Theme::bullet(),
                         Theme::span(card.file_path.display().to_string()),
                     ];
@@ -286,13 +668,21 @@ async fn start_drill_session(
                         header_vec.push(Theme::bullet());
                         header_vec.push(Theme::key_chip("AI enhanced"));
                     }
+                    if state.leeches.contains(&card.card_hash) {
+                        header_vec.push(Theme::bullet());
+                        header_vec.push(Theme::key_chip("Leech"));
+                    }
+                    if state.mode != DrillMode::Schedule {
+                        header_vec.push(Theme::bullet());
+                        header_vec.push(Theme::key_chip("Cram"));
+                    }
                     let header_line = Line::from(header_vec);
 
                     let ai_pending = state.current_ai_pending();
                     let content = if ai_pending {
                         "Enhancing this card with AI...\n\nPlease wait.".to_string()
                     } else {
-                        format_card_text(&card, state.show_answer)
+                        format_card_text(&card, state.show_hint, state.show_answer)
                     };
                     let markdown = render_markdown(&content);
                     state.current_medias = extract_media(&content, card.file_path.parent());
@@ -301,11 +691,6 @@ async fn start_drill_session(
                         .block(Theme::panel_with_line(header_line))
                         .wrap(Wrap { trim: false });
                     frame.render_widget(card_widget, chunks[0]);
-
-                    let instructions = instructions_text(&state);
-                    let footer = Paragraph::new(instructions)
-                        .block(Theme::panel_with_line(Theme::section_header("Controls")));
-                    frame.render_widget(footer, chunks[1]);
                 })
                 .context("failed to render frame")?;
 
@@ -322,17 +707,46 @@ async fn start_drill_session(
                 {
                     break Ok(());
                 }
+                if state.is_complete() {
+                    if matches!(key.code, KeyCode::Char('U' | 'u')) {
+                        state.undo_last().await?;
+                        continue;
+                    }
+                    break Ok(());
+                }
                 let ai_pending = state.current_ai_pending();
+                let waiting = state.current_card().is_none();
+                let cram = state.mode != DrillMode::Schedule;
                 match key.code {
-                    KeyCode::Char(' ') | KeyCode::Enter if !ai_pending => {
+                    KeyCode::Char(' ') | KeyCode::Enter if !ai_pending && !waiting => {
                         if !state.show_answer {
-                            state.reveal_answer();
+                            state.reveal_answer().await?;
                         } else {
-                            state.handle_review(ReviewStatus::Pass).await?;
+                            state.handle_review(ReviewStatus::Good).await?;
                         }
                     }
                     KeyCode::Char('F') | KeyCode::Char('f') if state.show_answer && !ai_pending => {
-                        state.handle_review(ReviewStatus::Fail).await?;
+                        state.handle_review(ReviewStatus::Again).await?;
+                    }
+                    KeyCode::Char(digit @ '1'..='4') if state.show_answer && !ai_pending => {
+                        if let Some(status) = digit.to_digit(10).and_then(ReviewStatus::from_rating)
+                        {
+                            state.handle_review(status).await?;
+                        }
+                    }
+                    KeyCode::Char('H') | KeyCode::Char('h')
+                        if !ai_pending && !waiting && !state.show_answer =>
+                    {
+                        state.show_hint = true;
+                    }
+                    KeyCode::Char('U') | KeyCode::Char('u') => {
+                        state.undo_last().await?;
+                    }
+                    KeyCode::Char('B') | KeyCode::Char('b') if !ai_pending && !waiting && !cram => {
+                        state.bury_current().await?;
+                    }
+                    KeyCode::Char('S') | KeyCode::Char('s') if !ai_pending && !waiting && !cram => {
+                        state.suspend_current().await?;
                     }
                     KeyCode::Char('O') | KeyCode::Char('o')
                         if !ai_pending
@@ -350,10 +764,46 @@ async fn start_drill_session(
     .await;
 
     teardown_terminal(&mut terminal)?;
+    if state.mode != DrillMode::Schedule {
+        print_cram_report(&state.crammed);
+    }
 
     loop_result
 }
 
+fn print_cram_report(crammed: &[CrammedCard]) {
+    let (failed, passed): (Vec<&CrammedCard>, Vec<&CrammedCard>) =
+        crammed.iter().partition(|crammed| crammed.grade.is_fail());
+    println!(
+        "{} {}",
+        Palette::paint(Palette::INFO, "Cram score:"),
+        Palette::paint(Palette::SUCCESS, format_score(passed.len(), crammed.len()))
+    );
+    if failed.is_empty() {
+        return;
+    }
+    println!(
+        "{}",
+        Palette::paint(
+            Palette::WARNING,
+            format!("Missed {}:", pluralize("card", failed.len()))
+        )
+    );
+    for crammed in failed {
+        println!("  {}", Palette::dim(crammed.card.location()));
+    }
+}
+
+fn format_score(passed: usize, total: usize) -> String {
+    if total == 0 {
+        return "no cards graded".to_string();
+    }
+    format!(
+        "{passed}/{total} passed ({:.0}%)",
+        passed as f64 * 100.0 / total as f64
+    )
+}
+
 fn teardown_terminal(terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>) -> Result<()> {
     disable_raw_mode().context("failed to disable raw mode")?;
     execute!(
@@ -377,33 +827,87 @@ fn instructions_text(state: &DrillState<'_>) -> Vec<Line<'static>> {
             Theme::key_chip("Ctrl+C"),
             Theme::span(" exit"),
         ]));
+    } else if state.is_complete() {
+        let mut line = vec![Theme::span("Session finished")];
+        push_undo_hint(state, &mut line);
+        line.extend([Theme::bullet(), Theme::span("any other key exits")]);
+        lines.push(Line::from(line));
+    } else if state.waiting_until().is_some() {
+        let mut line = vec![Theme::span("Waiting for the next learning step")];
+        push_undo_hint(state, &mut line);
+        line.extend([
+            Theme::bullet(),
+            Theme::key_chip("Esc"),
+            Theme::span(" / "),
+            Theme::key_chip("Ctrl+C"),
+            Theme::span(" exit"),
+        ]);
+        lines.push(Line::from(line));
     } else if state.show_answer {
-        lines.push(Line::from(vec![
+        let mut grades = Vec::new();
+        for status in ReviewStatus::ALL {
+            if !grades.is_empty() {
+                grades.push(Theme::bullet());
+            }
+            grades.push(Theme::key_chip(status.rating().to_string()));
+            grades.push(Span::styled(
+                format!(" {}", status.label()),
+                grade_style(status),
+            ));
+            if let Some((_, interval, days)) = state
+                .interval_previews
+                .iter()
+                .find(|(preview_status, _, _)| *preview_status == status)
+            {
+                grades.push(Theme::span(format!(
+                    " {}",
+                    format_interval(*interval, *days)
+                )));
+            }
+        }
+        lines.push(Line::from(grades));
+        let mut line = vec![
             Theme::key_chip("Space"),
             Theme::span(" or "),
             Theme::key_chip("Enter"),
-            Span::styled(" Pass", Theme::success()),
+            Span::styled(" Good", Theme::success()),
             Theme::bullet(),
             Theme::key_chip("F"),
-            Span::styled(" Fail", Theme::danger()),
+            Span::styled(" Again", Theme::danger()),
+        ];
+        push_skip_hints(state, &mut line);
+        push_undo_hint(state, &mut line);
+        line.extend([
             Theme::bullet(),
             Theme::key_chip("Esc"),
             Theme::span(" / "),
             Theme::key_chip("Ctrl+C"),
             Theme::span(" exit"),
-        ]));
+        ]);
+        lines.push(Line::from(line));
     } else {
         let mut line = vec![
             Theme::key_chip("Space"),
             Theme::span(" or "),
             Theme::key_chip("Enter"),
             Theme::span(" show answer"),
+        ];
+        if !state.show_hint
+            && state
+                .current_card()
+                .is_some_and(|card| card.content.hint().is_some())
+        {
+            line.extend([Theme::bullet(), Theme::key_chip("H"), Theme::span(" hint")]);
+        }
+        push_skip_hints(state, &mut line);
+        push_undo_hint(state, &mut line);
+        line.extend([
             Theme::bullet(),
             Theme::key_chip("Esc"),
             Theme::span(" / "),
             Theme::key_chip("Ctrl+C"),
             Theme::span(" exit"),
-        ];
+        ]);
         if !state.current_medias.is_empty() {
             let num_media = state.current_medias.len();
             line.push(Theme::bullet());
@@ -420,36 +924,155 @@ fn instructions_text(state: &DrillState<'_>) -> Vec<Line<'static>> {
     if let Some(action) = &state.last_action
         && action.last_reviewed_at.elapsed().as_secs_f64() < FLASH_SECS
     {
-        let style = match action.action {
-            ReviewStatus::Pass => Theme::success(),
-            ReviewStatus::Fail => Theme::danger(),
-        };
         lines.push(Line::from(vec![
             Theme::span("Last:"),
-            Span::styled(action.print(), style),
+            Span::styled(action.print(), grade_style(action.action)),
         ]));
     }
 
     lines
 }
 
-fn format_card_text(card: &Card, show_answer: bool) -> String {
-    match &card.content {
-        CardContent::Basic { question, answer } => {
-            let mut text = format!("Q:\n{}\n\nA:\n", question);
+/// Countdown shown when only learning cards are left and none is due yet.
+fn waiting_panel(state: &DrillState<'_>) -> Paragraph<'static> {
+    let remaining = state
+        .waiting_until()
+        .map(|due| due.signed_duration_since(Utc::now()))
+        .unwrap_or_default();
+    let lines = vec![
+        Line::from(vec![
+            Theme::span("Next card in "),
+            Theme::label_span(format_countdown(remaining)),
+        ]),
+        Line::from(""),
+        Line::from(vec![Theme::span(format!(
+            "{} waiting on a learning step. Reviewing before the step has passed would skew the scheduling.",
+            pluralize("card", state.learning.len())
+        ))]),
+    ];
+    Paragraph::new(lines)
+        .block(Theme::panel_with_line(Theme::title_line(
+            "All caught up for now",
+        )))
+        .wrap(Wrap { trim: false })
+}
+
+/// Shown once every card is graded, so the last grade can still be undone.
+fn finished_panel(state: &DrillState<'_>) -> Paragraph<'static> {
+    let graded = match state.mode {
+        DrillMode::Schedule => state.history.len(),
+        DrillMode::Cram { .. } => state.crammed.len(),
+    };
+    let lines = vec![
+        Line::from(vec![
+            Theme::span("Graded "),
+            Theme::label_span(pluralize("card", graded)),
+            Theme::span(" this session."),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Theme::span("Press "),
+            Theme::key_chip("U"),
+            Theme::span(" to take back the last grade, or any other key to exit."),
+        ]),
+    ];
+    Paragraph::new(lines)
+        .block(Theme::panel_with_line(Theme::title_line(
+            "Session finished",
+        )))
+        .wrap(Wrap { trim: false })
+}
+
+fn format_countdown(remaining: chrono::Duration) -> String {
+    let seconds = remaining.num_seconds().max(0);
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Bury and suspend change scheduling, so cram sessions leave them out.
+fn push_skip_hints(state: &DrillState<'_>, line: &mut Vec<Span<'static>>) {
+    if state.mode != DrillMode::Schedule {
+        return;
+    }
+    line.extend([
+        Theme::bullet(),
+        Theme::key_chip("B"),
+        Theme::span(" bury"),
+        Theme::bullet(),
+        Theme::key_chip("S"),
+        Theme::span(" suspend"),
+    ]);
+}
+
+fn push_undo_hint(state: &DrillState<'_>, line: &mut Vec<Span<'static>>) {
+    if state.can_undo() {
+        line.push(Theme::bullet());
+        line.push(Theme::key_chip("U"));
+        line.push(Theme::span(" undo"));
+    }
+}
+
+fn grade_style(status: ReviewStatus) -> Style {
+    match status {
+        ReviewStatus::Again => Theme::danger(),
+        ReviewStatus::Hard => Theme::emphasis(),
+        ReviewStatus::Good => Theme::success(),
+        ReviewStatus::Easy => Theme::label(),
+    }
+}
+
+/// Sub-day intervals in minutes or hours; longer ones by the `study_days` until the card
+/// is due, so the count matches the day it turns up on.
+fn format_interval(interval_days: f64, study_days: i64) -> String {
+    let minutes = interval_days * MINUTES_PER_DAY;
+    let days = study_days.max(1);
+    if minutes < 60.0 {
+        format!("{}m", minutes.round().max(1.0) as i64)
+    } else if minutes < MINUTES_PER_DAY {
+        format!("{}h", (minutes / 60.0).round() as i64)
+    } else if days < 30 {
+        format!("{days}d")
+    } else if days < 365 {
+        format!("{:.1}mo", days as f64 / 30.0)
+    } else {
+        format!("{:.1}y", days as f64 / 365.0)
+    }
+}
+
+fn format_card_text(card: &Card, show_hint: bool, show_answer: bool) -> String {
+    let hint = card.content.hint().filter(|_| show_hint);
+    let mut text = match &card.content {
+        CardContent::Basic {
+            question, answer, ..
+        } => {
+            let mut text = format!("Q:\n{}\n\n", question);
+            if let Some(hint) = hint {
+                text.push_str(&format!("Hint:\n{}\n\n", hint));
+            }
+            text.push_str("A:\n");
             if show_answer {
                 text.push_str(answer);
             }
             text
         }
-        CardContent::Cloze { text, cloze_range } => {
+        CardContent::Cloze {
+            text, cloze_range, ..
+        } => {
             let body = match (cloze_range, show_answer) {
                 (Some(range), false) => mask_cloze_text(text, range),
-                _ => text.clone(),
+                (Some(range), true) => reveal_cloze_text(text, range),
+                (None, _) => text.clone(),
             };
-            format!("C:\n{}", body)
+            let mut text = format!("C:\n{}", body);
+            if let Some(hint) = hint {
+                text.push_str(&format!("\n\nHint:\n{}", hint));
+            }
+            text
         }
+    };
+    if show_answer && let Some(notes) = card.content.notes() {
+        text.push_str(&format!("\n\nNotes:\n{}", notes));
     }
+    text
 }
 
 async fn preprocess_cards_in_order(
@@ -482,8 +1105,10 @@ async fn preprocess_cards_in_order(
 #[cfg(test)]
 mod tests {
     use crate::card::ClozeRange;
+    use crate::fsrs::Performance;
 
     use super::*;
+    use std::collections::HashMap;
     use std::path::PathBuf;
     use std::time::Instant;
 
@@ -491,6 +1116,8 @@ mod tests {
         let content = CardContent::Basic {
             question: question.into(),
             answer: answer.into(),
+            hint: None,
+            notes: None,
         };
         Card::new(PathBuf::from("test.md"), (0, 1), content, "hash".into())
     }
@@ -504,6 +1131,8 @@ mod tests {
             CardContent::Cloze {
                 text: text.into(),
                 cloze_range: Some(ClozeRange::new(start, end).unwrap()),
+                hint: None,
+                notes: None,
             },
             "hash".into(),
         )
@@ -513,10 +1142,10 @@ mod tests {
     fn basic_card_hides_answer_until_revealed() {
         let card = basic_card("What?", "Answer");
 
-        let hidden = format_card_text(&card, false);
+        let hidden = format_card_text(&card, false, false);
         assert!(!hidden.contains("Answer"));
 
-        let shown = format_card_text(&card, true);
+        let shown = format_card_text(&card, false, true);
         assert!(shown.contains("Answer"));
     }
 
@@ -524,69 +1153,135 @@ mod tests {
     fn cloze_card_masks_until_answer_shown() {
         let card = cloze_card("Value [東京]");
 
-        let masked = format_card_text(&card, false);
+        let masked = format_card_text(&card, false, false);
         let placeholder = extract_placeholder(&masked);
         assert!(placeholder.chars().all(|c| c == '_'));
         assert!(placeholder.chars().count() >= 3);
 
-        let revealed = format_card_text(&card, true);
+        let revealed = format_card_text(&card, false, true);
         assert!(revealed.contains("[東京]"));
     }
 
+    #[test]
+    fn hint_on_request_and_notes_with_the_answer() {
+        let mut card = basic_card("Capital of Japan?", "Tokyo");
+        card.content = CardContent::Basic {
+            question: "Capital of Japan?".into(),
+            answer: "Tokyo".into(),
+            hint: Some("Starts with T".into()),
+            notes: Some("Capital since 1868".into()),
+        };
+
+        let question = format_card_text(&card, false, false);
+        assert!(!question.contains("Starts with T"));
+        assert!(!question.contains("1868"));
+
+        let hinted = format_card_text(&card, true, false);
+        assert!(hinted.contains("Starts with T"));
+        assert!(!hinted.contains("Tokyo"));
+
+        let answered = format_card_text(&card, false, true);
+        assert!(answered.contains("Tokyo"));
+        assert!(answered.contains("Capital since 1868"));
+    }
+
     #[test]
     fn last_action_prints_human_friendly_intervals() {
         fn formatted(minutes: f64, status: ReviewStatus) -> String {
             let action = LastAction {
                 action: status,
-                show_again_duration: minutes / MINUTES_PER_DAY,
+                show_again_duration: Some(minutes / MINUTES_PER_DAY),
+                show_again_days: (minutes / MINUTES_PER_DAY).round() as i64,
                 last_reviewed_at: Instant::now(),
             };
             action.print()
         }
 
         assert_eq!(
-            formatted(10.0, ReviewStatus::Pass),
-            " Pass (See again in <15 mins)"
+            formatted(10.0, ReviewStatus::Good),
+            " Good (See again in <15 mins)"
         );
         assert_eq!(
-            formatted(20.0, ReviewStatus::Pass),
-            " Pass (See again in <30 mins)"
+            formatted(20.0, ReviewStatus::Hard),
+            " Hard (See again in <30 mins)"
         );
         assert_eq!(
-            formatted(60.0, ReviewStatus::Pass),
-            " Pass (See again in <12 hours)"
+            formatted(60.0, ReviewStatus::Good),
+            " Good (See again in <12 hours)"
         );
         assert_eq!(
-            formatted(22.0 * 60.0, ReviewStatus::Pass),
-            " Pass (See again in <1 day)"
+            formatted(22.0 * 60.0, ReviewStatus::Good),
+            " Good (See again in <1 day)"
         );
         assert_eq!(
-            formatted(3.0 * MINUTES_PER_DAY, ReviewStatus::Fail),
-            " Fail (See again in 3 days)"
+            formatted(3.0 * MINUTES_PER_DAY, ReviewStatus::Again),
+            " Again (See again in 3 days)"
         );
+
+        // a day and a bit, due before the next rollover but one
+        let action = LastAction {
+            action: ReviewStatus::Good,
+            show_again_duration: Some(1.2),
+            show_again_days: 1,
+            last_reviewed_at: Instant::now(),
+        };
+        assert_eq!(action.print(), " Good (See again in 1 day)");
     }
 
     #[test]
-    fn instructions_show_answer_branch_includes_pass_and_fail() {
+    fn instructions_show_answer_branch_includes_all_grades() {
         let db = in_memory_db();
-        let mut state = DrillState::new(&db, vec![basic_card("Q", "A")], 0.9);
+        let mut state = DrillState::new(
+            &db,
+            vec![basic_card("Q", "A")],
+            ReviewOptions::default(),
+            DayBoundary::default(),
+        );
         state.show_answer = true;
+        state.interval_previews = vec![
+            (ReviewStatus::Again, 1.0 / MINUTES_PER_DAY, 0),
+            (ReviewStatus::Hard, 6.0 / MINUTES_PER_DAY, 0),
+            (ReviewStatus::Good, 10.0 / MINUTES_PER_DAY, 0),
+            (ReviewStatus::Easy, 4.0, 4),
+        ];
 
         let lines = instructions_text(&state);
-        let commands = flatten_line(&lines[0]);
+        let grades = flatten_line(&lines[0]);
+
+        assert!(grades.contains("Again 1m"));
+        assert!(grades.contains("Hard 6m"));
+        assert!(grades.contains("Good 10m"));
+        assert!(grades.contains("Easy 4d"));
+    }
 
-        assert!(commands.contains("Pass"));
-        assert!(commands.contains("Fail"));
+    #[test]
+    fn format_interval_uses_compact_units() {
+        assert_eq!(format_interval(0.0, 0), "1m");
+        assert_eq!(format_interval(10.0 / MINUTES_PER_DAY, 0), "10m");
+        assert_eq!(format_interval(0.25, 0), "6h");
+        assert_eq!(format_interval(0.25, 1), "6h");
+        assert_eq!(format_interval(7.3, 7), "7d");
+        // counted in study days, not 24-hour periods
+        assert_eq!(format_interval(1.9, 1), "1d");
+        assert_eq!(format_interval(1.2, 2), "2d");
+        assert_eq!(format_interval(45.0, 45), "1.5mo");
+        assert_eq!(format_interval(730.0, 730), "2.0y");
     }
 
     #[test]
     fn recent_last_action_is_displayed_in_instructions() {
         let db = in_memory_db();
-        let mut state = DrillState::new(&db, vec![basic_card("Q", "A")], 0.9);
+        let mut state = DrillState::new(
+            &db,
+            vec![basic_card("Q", "A")],
+            ReviewOptions::default(),
+            DayBoundary::default(),
+        );
         state.show_answer = true;
         state.last_action = Some(LastAction {
-            action: ReviewStatus::Fail,
-            show_again_duration: 0.0,
+            action: ReviewStatus::Again,
+            show_again_duration: Some(0.0),
+            show_again_days: 0,
             last_reviewed_at: Instant::now(),
         });
 
@@ -595,7 +1290,228 @@ mod tests {
 
         let last_line = flatten_line(lines.last().unwrap());
         assert!(last_line.contains("Last:"));
-        assert!(last_line.contains("Fail"));
+        assert!(last_line.contains("Again"));
+    }
+
+    #[tokio::test]
+    async fn bury_and_suspend_skip_the_card() {
+        let db = DB::new_in_memory().await.unwrap();
+        let first = basic_card("Q1", "A1");
+        let mut second = basic_card("Q2", "A2");
+        second.card_hash = "hash2".into();
+        db.add_cards_batch(&[first.clone(), second.clone()])
+            .await
+            .unwrap();
+        let mut state = DrillState::new(
+            &db,
+            vec![first.clone(), second.clone()],
+            ReviewOptions::default(),
+            DayBoundary::default(),
+        );
+        state.schedule_learning(first.clone(), Utc::now());
+
+        state.bury_current().await.unwrap();
+        assert!(state.learning.is_empty());
+        assert_eq!(state.current_card().unwrap().card_hash, second.card_hash);
+
+        state.suspend_current().await.unwrap();
+        assert!(state.is_complete());
+
+        let card_hashes = HashMap::from([
+            (first.card_hash.clone(), first),
+            (second.card_hash.clone(), second),
+        ]);
+        let stats = db
+            .collection_stats(&card_hashes, DayBoundary::default())
+            .await
+            .unwrap();
+        assert_eq!(stats.buried_cards, 1);
+        assert_eq!(stats.suspended_cards, 1);
+    }
+
+    #[tokio::test]
+    async fn undo_still_works_after_a_card_is_skipped() {
+        let db = DB::new_in_memory().await.unwrap();
+        let first = basic_card("Q1", "A1");
+        let mut second = basic_card("Q2", "A2");
+        second.card_hash = "hash2".into();
+        let mut third = basic_card("Q3", "A3");
+        third.card_hash = "hash3".into();
+        let cards = vec![first.clone(), second.clone(), third.clone()];
+        db.add_cards_batch(&cards).await.unwrap();
+        let mut state =
+            DrillState::new(&db, cards, ReviewOptions::default(), DayBoundary::default());
+
+        state.handle_review(ReviewStatus::Good).await.unwrap();
+        state.bury_current().await.unwrap();
+        assert_eq!(state.current_card().unwrap().card_hash, third.card_hash);
+        assert!(state.can_undo());
+
+        assert!(state.undo_last().await.unwrap());
+        assert_eq!(state.current_card().unwrap().card_hash, first.card_hash);
+        let queue: Vec<&str> = state
+            .cards
+            .iter()
+            .map(|card| card.card_hash.as_str())
+            .collect();
+        assert_eq!(queue, vec!["hash", "hash3"]);
+
+        state.handle_review(ReviewStatus::Good).await.unwrap();
+        assert_eq!(state.current_card().unwrap().card_hash, third.card_hash);
+    }
+
+    #[tokio::test]
+    async fn undo_steps_back_through_several_reviews() {
+        let db = DB::new_in_memory().await.unwrap();
+        let first = basic_card("Q1", "A1");
+        let mut second = basic_card("Q2", "A2");
+        second.card_hash = "hash2".into();
+        db.add_cards_batch(&[first.clone(), second.clone()])
+            .await
+            .unwrap();
+        let mut state = DrillState::new(
+            &db,
+            vec![first.clone(), second.clone()],
+            ReviewOptions::default(),
+            DayBoundary::default(),
+        );
+
+        state.handle_review(ReviewStatus::Again).await.unwrap();
+        state.handle_review(ReviewStatus::Easy).await.unwrap();
+        assert_eq!(state.learning.len(), 1);
+        assert!(state.current_card().is_none());
+
+        assert!(state.undo_last().await.unwrap());
+        assert_eq!(state.current_card().unwrap().card_hash, second.card_hash);
+        assert_eq!(
+            db.get_card_performance(&second).await.unwrap(),
+            Performance::New
+        );
+
+        assert!(state.undo_last().await.unwrap());
+        assert_eq!(state.current_idx, 0);
+        assert!(state.learning.is_empty());
+        assert_eq!(state.current_card().unwrap().card_hash, first.card_hash);
+        assert_eq!(
+            db.get_card_performance(&first).await.unwrap(),
+            Performance::New
+        );
+        assert!(db.review_log().await.unwrap().is_empty());
+
+        assert!(!state.undo_last().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn last_review_of_a_session_can_be_undone() {
+        let db = DB::new_in_memory().await.unwrap();
+        let card = basic_card("Q1", "A1");
+        db.add_cards_batch(std::slice::from_ref(&card))
+            .await
+            .unwrap();
+        let mut state = DrillState::new(
+            &db,
+            vec![card.clone()],
+            ReviewOptions::default(),
+            DayBoundary::default(),
+        );
+
+        state.handle_review(ReviewStatus::Easy).await.unwrap();
+        assert!(state.is_complete());
+        assert!(state.can_undo());
+        let footer = flatten_line(&instructions_text(&state)[0]);
+        assert!(footer.contains("Session finished"));
+        assert!(footer.contains("undo"));
+
+        assert!(state.undo_last().await.unwrap());
+        assert!(!state.is_complete());
+        assert_eq!(state.current_card().unwrap().card_hash, card.card_hash);
+        assert_eq!(
+            db.get_card_performance(&card).await.unwrap(),
+            Performance::New
+        );
+    }
+
+    #[tokio::test]
+    async fn cram_leaves_scheduling_alone() {
+        let db = DB::new_in_memory().await.unwrap();
+        let first = basic_card("Q1", "A1");
+        let mut second = basic_card("Q2", "A2");
+        second.card_hash = "hash2".into();
+        db.add_cards_batch(&[first.clone(), second.clone()])
+            .await
+            .unwrap();
+        let mut state = DrillState::new(
+            &db,
+            vec![first.clone(), second.clone()],
+            ReviewOptions::default(),
+            DayBoundary::default(),
+        );
+        state.mode = DrillMode::Cram { log: true };
+
+        state.reveal_answer().await.unwrap();
+        assert!(state.interval_previews.is_empty());
+        state.handle_review(ReviewStatus::Again).await.unwrap();
+        state.handle_review(ReviewStatus::Good).await.unwrap();
+        assert!(state.is_complete());
+        assert_eq!(state.crammed.len(), 2);
+        for card in [&first, &second] {
+            assert_eq!(
+                db.get_card_performance(card).await.unwrap(),
+                Performance::New
+            );
+        }
+        assert!(db.review_log().await.unwrap().is_empty());
+
+        assert!(state.undo_last().await.unwrap());
+        assert_eq!(state.current_card().unwrap().card_hash, second.card_hash);
+        assert_eq!(state.crammed.len(), 1);
+    }
+
+    #[test]
+    fn format_score_shows_the_pass_rate() {
+        assert_eq!(format_score(9, 12), "9/12 passed (75%)");
+        assert_eq!(format_score(0, 0), "no cards graded");
+    }
+
+    #[tokio::test]
+    async fn learning_cards_wait_for_their_step() {
+        let db = DB::new_in_memory().await.unwrap();
+        let first = basic_card("Q1", "A1");
+        let mut second = basic_card("Q2", "A2");
+        second.card_hash = "hash2".into();
+        db.add_cards_batch(&[first.clone(), second.clone()])
+            .await
+            .unwrap();
+        let mut state = DrillState::new(
+            &db,
+            vec![first.clone(), second.clone()],
+            ReviewOptions::default(),
+            DayBoundary::default(),
+        );
+
+        // the 1 minute step has not passed, so the session moves on to the next due card
+        state.handle_review(ReviewStatus::Again).await.unwrap();
+        assert_eq!(state.current_card().unwrap().card_hash, second.card_hash);
+        let due = state.learning[0].due;
+        assert!(due > Utc::now() + chrono::Duration::seconds(30));
+
+        // then waits rather than showing it early
+        state.handle_review(ReviewStatus::Easy).await.unwrap();
+        assert!(state.current_card().is_none());
+        assert!(!state.is_complete());
+        assert_eq!(state.waiting_until(), Some(due));
+        assert!(!state.promote_due_learning(Utc::now()));
+        assert!(flatten_line(&instructions_text(&state)[0]).contains("Waiting"));
+
+        assert!(state.promote_due_learning(due));
+        assert_eq!(state.current_card().unwrap().card_hash, first.card_hash);
+        assert_eq!(state.waiting_until(), None);
+    }
+
+    #[test]
+    fn format_countdown_uses_minutes_and_seconds() {
+        assert_eq!(format_countdown(chrono::Duration::seconds(65)), "1:05");
+        assert_eq!(format_countdown(chrono::Duration::seconds(-3)), "0:00");
     }
 
     fn extract_placeholder(text: &str) -> String {
@@ -0,0 +1,254 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::card::Card;
+use crate::crud::DB;
+use crate::palette::Palette;
+use crate::parser::{
+    cards_from_text, format_card_id, markdown_files, new_card_id, register_all_cards, sibling_hash,
+    strip_card_id,
+};
+use crate::utils::pluralize;
+
+struct Deck {
+    path: PathBuf,
+    text: String,
+    cards: Vec<Card>,
+}
+
+/// Report which cards have stable IDs, and with `assign`, write IDs into the cards that
+/// don't, keeping their history.
+pub async fn run(db: &DB, paths: Vec<PathBuf>, assign: bool) -> Result<()> {
+    // registering first gives every card a row for its history to move from
+    register_all_cards(db, paths.clone()).await?;
+
+    let mut decks = Vec::new();
+    for path in markdown_files(&paths)? {
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let cards = cards_from_text(&path, &text)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        decks.push(Deck { path, text, cards });
+    }
+
+    let mut locations: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut missing = 0;
+    for deck in &decks {
+        // siblings share their first deletion's id
+        for card in deck.cards.iter().filter(|card| card.sibling == 0) {
+            match card_id(&deck.text, card)? {
+                Some(id) => locations.entry(id).or_default().push(card.location()),
+                None => missing += 1,
+            }
+        }
+    }
+    for (id, places) in locations.iter().filter(|(_, places)| places.len() > 1) {
+        println!(
+            "{} {} {}",
+            Palette::paint(Palette::WARNING, "Duplicate id"),
+            Palette::paint(Palette::DANGER, id),
+            Palette::dim(format!("at {}", places.join(", ")))
+        );
+    }
+
+    if !assign {
+        let with_ids: usize = locations.values().map(Vec::len).sum();
+        println!(
+            "{} {}",
+            Palette::paint(Palette::INFO, pluralize("card", with_ids)),
+            Palette::dim("have ids")
+        );
+        println!(
+            "{} {}",
+            Palette::paint(Palette::INFO, pluralize("card", missing)),
+            Palette::dim("don't; run with --assign to add them")
+        );
+        return Ok(());
+    }
+
+    let mut taken: HashSet<String> = locations.into_keys().collect();
+    let mut assigned = 0;
+    let mut moved = 0;
+    for deck in &decks {
+        let (text, moves) = assign_ids(&deck.text, &deck.cards, &mut taken)?;
+        if moves.is_empty() {
+            continue;
+        }
+        fs::write(&deck.path, text)
+            .with_context(|| format!("Failed to write {}", deck.path.display()))?;
+        moved += db.move_card_history(&moves).await?;
+        assigned += moves.len();
+        println!(
+            "  {} {}",
+            Palette::dim(deck.path.display()),
+            Palette::paint(Palette::INFO, pluralize("card", moves.len()))
+        );
+    }
+    println!(
+        "{} {} {}",
+        Palette::paint(Palette::SUCCESS, "Assigned ids to"),
+        Palette::paint(Palette::INFO, pluralize("card", assigned)),
+        Palette::dim(format!("(history kept for {moved})"))
+    );
+    Ok(())
+}
+
+/// Lines of `text` that make up `card`.
+fn card_lines(card: &Card, line_count: usize) -> Range<usize> {
+    let (start, end) = card.file_card_range;
+    // single-line `::` cards start and end on the same line
+    let end = if end == start { start + 1 } else { end };
+    start.min(line_count)..end.min(line_count)
+}
+
+fn card_id(text: &str, card: &Card) -> Result<Option<String>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let range = card_lines(card, lines.len());
+    let (_, id) = strip_card_id(&lines[range].join("\n"))?;
+    Ok(id)
+}
+
+/// Add an id comment to every card in `text` that lacks one: after the last line of
+/// a multi-line card, or at the end of a one-line card. Returns the new text and the
+/// `(content hash, id)` pairs to move history along.
+fn assign_ids(
+    text: &str,
+    cards: &[Card],
+    taken: &mut HashSet<String>,
+) -> Result<(String, Vec<(String, String)>)> {
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let mut moves = Vec::new();
+    let mut edits: Vec<(usize, bool, String)> = Vec::new();
+    for card in cards.iter().filter(|card| card.sibling == 0) {
+        if card_id(text, card)?.is_some() {
+            continue;
+        }
+        let id = new_card_id(taken);
+        taken.insert(id.clone());
+        let range = card_lines(card, lines.len());
+        let Some(last) = range
+            .clone()
+            .rev()
+            .find(|idx| !lines[*idx].trim().is_empty())
+        else {
+            continue;
+        };
+        edits.push((last, range.len() == 1, format_card_id(&id)));
+        for sibling in cards
+            .iter()
+            .filter(|other| other.card_hash == card.card_hash || other.is_sibling_of(card))
+        {
+            moves.push((
+                sibling.card_hash.clone(),
+                sibling_hash(&id, sibling.sibling),
+            ));
+        }
+    }
+
+    edits.sort_by_key(|(line, _, _)| *line);
+    for (line, inline, comment) in edits.into_iter().rev() {
+        if inline {
+            let trimmed = lines[line].trim_end().len();
+            lines[line].truncate(trimmed);
+            lines[line].push(' ');
+            lines[line].push_str(&comment);
+        } else {
+            lines.insert(line + 1, comment);
+        }
+    }
+
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut out = lines.join(newline);
+    if text.ends_with('\n') {
+        out.push_str(newline);
+    }
+    Ok((out, moves))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::crud::cards::ReviewOptions;
+    use crate::fsrs::{Performance, ReviewStatus};
+
+    #[test]
+    fn ids_go_after_each_card() {
+        let text = "# Geo\nItaly::Rome\n\nQ: Capital of Japan?\nA: Tokyo\n\nQ: Capital of France?\nA: Paris\n<!-- id: fr -->\n";
+        let path = Path::new("geo.md");
+        let cards = cards_from_text(path, text).unwrap();
+        let mut taken = HashSet::from(["fr".to_string()]);
+        let (out, moves) = assign_ids(text, &cards, &mut taken).unwrap();
+        assert_eq!(moves.len(), 2);
+
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[1],
+            format!("Italy::Rome {}", format_card_id(&moves[0].1))
+        );
+        assert_eq!(lines[4], "A: Tokyo");
+        assert_eq!(lines[5], format_card_id(&moves[1].1));
+        assert_eq!(lines[9], "<!-- id: fr -->");
+        assert!(out.ends_with('\n'));
+
+        let reparsed = cards_from_text(path, &out).unwrap();
+        let hashes: Vec<&str> = reparsed.iter().map(|c| c.card_hash.as_str()).collect();
+        assert_eq!(hashes, vec![moves[0].1.as_str(), moves[1].1.as_str(), "fr"]);
+    }
+
+    #[test]
+    fn siblings_share_one_id() {
+        let text = "C: [ping] and [pong]\n";
+        let cards = cards_from_text(Path::new("deck.md"), text).unwrap();
+        let (out, moves) = assign_ids(text, &cards, &mut HashSet::new()).unwrap();
+        let reparsed = cards_from_text(Path::new("deck.md"), &out).unwrap();
+        let moved: Vec<(String, String)> = cards
+            .iter()
+            .zip(&reparsed)
+            .map(|(old, new)| (old.card_hash.clone(), new.card_hash.clone()))
+            .collect();
+        assert_eq!(moves, moved);
+        assert_eq!(out.matches("<!-- id:").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn assigning_ids_keeps_history() {
+        let db = DB::new_in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let deck = dir.path().join("deck.md");
+        fs::write(&deck, "Q: Capital of Japan?\nA: Tokyo\n").unwrap();
+        let paths = vec![dir.path().to_path_buf()];
+        let (card_hashes, _) = register_all_cards(&db, paths.clone()).await.unwrap();
+        let card = card_hashes.values().next().unwrap();
+        db.update_card_performance(
+            card,
+            ReviewStatus::Good,
+            None,
+            &ReviewOptions::default(),
+            std::time::Duration::ZERO,
+        )
+        .await
+        .unwrap();
+
+        run(&db, paths.clone(), true).await.unwrap();
+        // rewording no longer loses the card
+        let text = fs::read_to_string(&deck).unwrap();
+        fs::write(&deck, text.replace("Japan", "Nippon")).unwrap();
+
+        let (card_hashes, _) = register_all_cards(&db, paths).await.unwrap();
+        let card = card_hashes.values().next().unwrap();
+        assert!(matches!(
+            db.get_card_performance(card).await.unwrap(),
+            Performance::Reviewed(_)
+        ));
+        assert_eq!(
+            db.review_log_for_card(&card.card_hash).await.unwrap().len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::card::{Card, CardContent};
+use crate::crud::DB;
+use crate::palette::Palette;
+use crate::parser::register_all_cards;
+use crate::utils::pluralize;
+
+const SNIPPET_LIMIT: usize = 60;
+
+pub async fn run(db: &DB, paths: Vec<PathBuf>) -> Result<()> {
+    let leeches = collect_leeches(db, paths).await?;
+    if leeches.is_empty() {
+        println!(
+            "{}",
+            Palette::paint(Palette::SUCCESS, "No leeches—every card is sticking.")
+        );
+        return Ok(());
+    }
+
+    for (card, lapses, suspended) in &leeches {
+        let (start, end) = card.file_card_range;
+        let mut line = format!(
+            "{} {} {}",
+            Palette::paint(
+                Palette::INFO,
+                format!("{}:{}-{}", card.file_path.display(), start + 1, end + 1)
+            ),
+            Palette::paint(Palette::DANGER, pluralize("lapse", *lapses as usize)),
+            snippet(card)
+        );
+        if *suspended {
+            line.push_str(&format!(" {}", Palette::dim("(suspended)")));
+        }
+        println!("{line}");
+    }
+    println!(
+        "{} {}",
+        Palette::paint(Palette::WARNING, pluralize("leech", leeches.len())),
+        Palette::dim("— consider rewriting or splitting these cards.")
+    );
+    Ok(())
+}
+
+/// Registered leeches under `paths` with their lapse count and whether they are suspended,
+/// most lapses first.
+async fn collect_leeches(db: &DB, paths: Vec<PathBuf>) -> Result<Vec<(Card, i64, bool)>> {
+    let (card_hashes, _) = register_all_cards(db, paths).await?;
+    Ok(db
+        .leeches()
+        .await?
+        .into_iter()
+        .filter_map(|row| {
+            card_hashes
+                .get(&row.card_hash)
+                .map(|card| (card.clone(), row.lapses, row.suspended))
+        })
+        .collect())
+}
+
+fn snippet(card: &Card) -> String {
+    let text = match &card.content {
+        CardContent::Basic { question, .. } => question,
+        CardContent::Cloze { text, .. } => text,
+    };
+    let first_line = text.lines().next().unwrap_or_default().trim();
+    if first_line.chars().count() > SNIPPET_LIMIT {
+        let truncated: String = first_line.chars().take(SNIPPET_LIMIT).collect();
+        format!("{truncated}…")
+    } else {
+        first_line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::crud::cards::ReviewOptions;
+    use crate::crud::leeches::{LeechAction, LeechPolicy};
+    use crate::fsrs::ReviewStatus;
+
+    #[tokio::test]
+    async fn repeated_lapses_flag_a_leech() {
+        let db = DB::new_in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("deck.md"),
+            "Q: Capital of Japan?\nA: Tokyo\n\nQ: Capital of France?\nA: Paris",
+        )
+        .unwrap();
+        let paths = vec![dir.path().to_path_buf()];
+        let (card_hashes, _) = register_all_cards(&db, paths.clone()).await.unwrap();
+        let card = card_hashes
+            .values()
+            .find(|card| card.matches_query("tokyo"))
+            .unwrap()
+            .clone();
+        let options = ReviewOptions {
+            leech: LeechPolicy {
+                threshold: 2,
+                action: LeechAction::Suspend,
+            },
+            ..ReviewOptions::default()
+        };
+
+        let mut now = chrono::Utc::now();
+        let mut review = async |status| {
+            now += chrono::Duration::days(1);
+            db.update_card_performance(
+                &card,
+                status,
+                Some(now),
+                &options,
+                std::time::Duration::ZERO,
+            )
+            .await
+            .unwrap()
+        };
+
+        // failing a card that was never passed is not a lapse
+        assert_eq!(review(ReviewStatus::Again).await.lapses, 0);
+        review(ReviewStatus::Good).await;
+        let outcome = review(ReviewStatus::Again).await;
+        assert_eq!(outcome.lapses, 1);
+        assert!(!outcome.leech);
+        assert_eq!(review(ReviewStatus::Again).await.lapses, 1);
+        review(ReviewStatus::Good).await;
+        let outcome = review(ReviewStatus::Again).await;
+        assert_eq!(outcome.lapses, 2);
+        assert!(outcome.leech);
+        assert!(outcome.auto_suspended);
+
+        let leeches = collect_leeches(&db, paths).await.unwrap();
+        assert_eq!(leeches.len(), 1);
+        let (leech, lapses, suspended) = &leeches[0];
+        assert_eq!(leech.card_hash, card.card_hash);
+        assert_eq!(*lapses, 2);
+        assert!(*suspended);
+        assert_eq!(snippet(leech), "Capital of Japan?");
+    }
+}
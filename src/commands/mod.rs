@@ -1,3 +1,10 @@
+pub mod card;
 pub mod check;
 pub mod create;
 pub mod drill;
+pub mod ids;
+pub mod leeches;
+pub mod optimize;
+pub mod reschedule;
+pub mod simulate;
+pub mod suspend;
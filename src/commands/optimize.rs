@@ -0,0 +1,285 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use fsrs::{
+    CombinedProgressState, ComputeParametersInput, DEFAULT_PARAMETERS, FSRS, FSRSItem, FSRSReview,
+};
+
+use crate::crud::DB;
+use crate::crud::revlog::ReviewLogEntry;
+use crate::palette::Palette;
+use crate::utils::pluralize;
+
+/// Below this many training items the FSRS optimizer only adjusts the initial
+/// stabilities, so we tell the user to keep reviewing instead.
+const MIN_TRAINING_ITEMS: usize = 64;
+
+pub async fn run(db: &DB, reset: bool) -> Result<()> {
+    if reset {
+        if db.clear_fsrs_parameters().await? {
+            println!(
+                "{}",
+                Palette::paint(
+                    Palette::SUCCESS,
+                    "Removed personalized FSRS parameters; scheduling uses the defaults again."
+                )
+            );
+        } else {
+            println!(
+                "{}",
+                Palette::dim("No personalized FSRS parameters were stored.")
+            );
+        }
+        return Ok(());
+    }
+
+    let review_log = db.review_log().await?;
+    let items = fsrs_items_from_review_log(&review_log);
+    let num_cards = review_log
+        .iter()
+        .map(|entry| entry.card_hash.as_str())
+        .collect::<HashSet<_>>()
+        .len();
+
+    println!(
+        "{} {} {}",
+        Palette::dim("Training on"),
+        Palette::paint(Palette::INFO, pluralize("review", review_log.len())),
+        Palette::dim(format!("from {}", pluralize("card", num_cards)))
+    );
+
+    if items.len() < MIN_TRAINING_ITEMS {
+        println!(
+            "{}",
+            Palette::paint(
+                Palette::WARNING,
+                format!(
+                    "Not enough review history to optimize yet ({} of at least {} usable reviews). Keep drilling and try again later.",
+                    items.len(),
+                    MIN_TRAINING_ITEMS
+                )
+            )
+        );
+        return Ok(());
+    }
+
+    let parameters = compute_parameters(items).await?;
+    db.save_fsrs_parameters(&parameters, review_log.len() as i64)
+        .await?;
+
+    println!(
+        "{}",
+        Palette::paint(
+            Palette::SUCCESS,
+            "Saved personalized FSRS parameters. Future reviews will be scheduled with them."
+        )
+    );
+    println!(
+        "{} {}",
+        Palette::dim("Parameters:"),
+        Palette::paint(Palette::INFO, format_parameters(&parameters))
+    );
+    Ok(())
+}
+
+async fn compute_parameters(items: Vec<FSRSItem>) -> Result<Vec<f32>> {
+    let progress = CombinedProgressState::new_shared();
+    let input = ComputeParametersInput {
+        train_set: items,
+        progress: Some(progress.clone()),
+        ..Default::default()
+    };
+
+    let mut handle = tokio::task::spawn_blocking(move || {
+        let fsrs = FSRS::new(Some(&DEFAULT_PARAMETERS))?;
+        fsrs.compute_parameters(input)
+    });
+
+    let result = loop {
+        tokio::select! {
+            result = &mut handle => break result,
+            _ = tokio::time::sleep(Duration::from_millis(250)) => {
+                let percent = {
+                    let state = progress.lock().map_err(|_| anyhow!("progress lock poisoned"))?;
+                    (state.current() * 100).checked_div(state.total())
+                };
+                if let Some(percent) = percent {
+                    print!(
+                        "\r{} {}",
+                        Palette::dim("Optimizing"),
+                        Palette::paint(Palette::INFO, format!("{percent:>3}%"))
+                    );
+                    io::stdout().flush()?;
+                }
+            }
+        }
+    };
+    println!();
+
+    result
+        .context("FSRS optimization task failed")?
+        .context("failed to compute FSRS parameters")
+}
+
+/// Convert the review log into FSRS training items: one item per review after
+/// the first, each carrying the card's history up to that review. Items are
+/// sorted by review time, which the optimizer uses to weight recent reviews.
+pub fn fsrs_items_from_review_log(review_log: &[ReviewLogEntry]) -> Vec<FSRSItem> {
+    let mut items: Vec<(chrono::DateTime<chrono::Utc>, FSRSItem)> = Vec::new();
+
+    for card_log in review_log.chunk_by(|a, b| a.card_hash == b.card_hash) {
+        let mut reviews: Vec<FSRSReview> = Vec::with_capacity(card_log.len());
+        let mut previous: Option<chrono::DateTime<chrono::Utc>> = None;
+
+        for entry in card_log {
+            let delta_t = previous
+                .map(|prev| {
+                    (entry.reviewed_at.date_naive() - prev.date_naive())
+                        .num_days()
+                        .max(0) as u32
+                })
+                .unwrap_or(0);
+            reviews.push(FSRSReview {
+                rating: entry.grade.rating(),
+                delta_t,
+            });
+            previous = Some(entry.reviewed_at);
+
+            if reviews.len() > 1 {
+                items.push((
+                    entry.reviewed_at,
+                    FSRSItem {
+                        reviews: reviews.clone(),
+                    },
+                ));
+            }
+        }
+    }
+
+    items.sort_by_key(|(reviewed_at, _)| *reviewed_at);
+    items.into_iter().map(|(_, item)| item).collect()
+}
+
+fn format_parameters(parameters: &[f32]) -> String {
+    parameters
+        .iter()
+        .map(|w| format!("{w:.4}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::*;
+    use crate::fsrs::ReviewStatus;
+
+    fn entry(card_hash: &str, day: i64, grade: ReviewStatus) -> ReviewLogEntry {
+        ReviewLogEntry {
+            id: 0,
+            card_hash: card_hash.into(),
+            reviewed_at: Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap() + Duration::days(day),
+            grade,
+            elapsed_days: 0.0,
+            previous_stability: None,
+            previous_difficulty: None,
+            stability: 1.0,
+            difficulty: 5.0,
+            interval_raw: 1.0,
+            time_spent: std::time::Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn builds_items_with_card_history() {
+        let log = vec![
+            entry("a", 0, ReviewStatus::Good),
+            entry("a", 1, ReviewStatus::Good),
+            entry("a", 4, ReviewStatus::Again),
+            entry("b", 2, ReviewStatus::Easy),
+            entry("b", 12, ReviewStatus::Hard),
+        ];
+
+        let items = fsrs_items_from_review_log(&log);
+        assert_eq!(items.len(), 3);
+
+        // sorted by review time: a@1, a@4, b@12
+        assert_eq!(
+            items[0].reviews,
+            vec![
+                FSRSReview {
+                    rating: 3,
+                    delta_t: 0
+                },
+                FSRSReview {
+                    rating: 3,
+                    delta_t: 1
+                },
+            ]
+        );
+        assert_eq!(items[1].reviews.len(), 3);
+        assert_eq!(
+            items[1].reviews[2],
+            FSRSReview {
+                rating: 1,
+                delta_t: 3
+            }
+        );
+        assert_eq!(
+            items[2].reviews,
+            vec![
+                FSRSReview {
+                    rating: 4,
+                    delta_t: 0
+                },
+                FSRSReview {
+                    rating: 2,
+                    delta_t: 10
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn single_reviews_produce_no_items() {
+        let log = vec![
+            entry("a", 0, ReviewStatus::Good),
+            entry("b", 0, ReviewStatus::Again),
+        ];
+        assert!(fsrs_items_from_review_log(&log).is_empty());
+    }
+
+    #[tokio::test]
+    async fn optimize_without_history_keeps_defaults() {
+        let db = DB::new_in_memory().await.unwrap();
+        run(&db, false).await.unwrap();
+        assert!(db.stored_fsrs_parameters().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn trains_parameters_from_history() {
+        let mut items = Vec::new();
+        for card in 0..200u32 {
+            let mut reviews = vec![FSRSReview {
+                rating: 3,
+                delta_t: 0,
+            }];
+            let mut delta_t = 1;
+            for review in 0..6u32 {
+                let rating = if (card + review) % 7 == 0 { 1 } else { 3 };
+                reviews.push(FSRSReview { rating, delta_t });
+                items.push(FSRSItem {
+                    reviews: reviews.clone(),
+                });
+                delta_t = if rating == 1 { 1 } else { delta_t * 2 + 1 };
+            }
+        }
+
+        let parameters = compute_parameters(items).await.unwrap();
+        assert_eq!(parameters.len(), DEFAULT_PARAMETERS.len());
+        assert_ne!(parameters, DEFAULT_PARAMETERS.to_vec());
+    }
+}
@@ -0,0 +1,338 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::crud::DB;
+use crate::crud::cards::Reschedule;
+use crate::crud::stats::CardStatsRow;
+use crate::fsrs::decay;
+use crate::palette::Palette;
+use crate::parser::register_all_cards;
+use crate::utils::{DayBoundary, pluralize};
+
+#[derive(Clone, Debug, Default)]
+pub struct RescheduleOptions {
+    pub paths: Vec<PathBuf>,
+    /// Spread overdue cards over this many study days, starting today.
+    pub spread: Option<usize>,
+    /// First and last study day away; time in between does not count towards forgetting.
+    pub away: Option<(NaiveDate, NaiveDate)>,
+    pub day: DayBoundary,
+    pub dry_run: bool,
+}
+
+pub async fn run(db: &DB, opts: RescheduleOptions) -> Result<()> {
+    if opts.spread.is_none() && opts.away.is_none() {
+        bail!("Pass --spread, or --away-from and --away-until");
+    }
+    if opts.spread == Some(0) {
+        bail!("--spread needs at least one day");
+    }
+    let (card_hashes, _) = register_all_cards(db, opts.paths).await?;
+    let mut rows = db.card_stats_rows(&card_hashes).await?;
+    let now = Utc::now();
+
+    let mut changes = Vec::new();
+    if let Some((first, last)) = opts.away {
+        if last < first {
+            bail!("--away-until can't be before --away-from");
+        }
+        let from = opts.day.start_of(first);
+        let until = opts.day.start_of(last + Duration::days(1));
+        let frozen = freeze(&rows, from, until, now);
+        println!(
+            "{} {} {}",
+            Palette::paint(Palette::INFO, "Away"),
+            Palette::paint(
+                Palette::SUCCESS,
+                format!(
+                    "{} to {}",
+                    first.format("%Y-%m-%d"),
+                    last.format("%Y-%m-%d")
+                )
+            ),
+            Palette::dim(format!("shifts {}", pluralize("card", frozen.len())))
+        );
+        apply(&mut rows, &frozen);
+        changes.extend(frozen);
+    }
+    if let Some(days) = opts.spread {
+        let decay = decay(&db.fsrs_parameters().await?);
+        let spread = spread_overdue(&rows, days, decay, now, opts.day);
+        println!(
+            "{} {} {}",
+            Palette::paint(Palette::INFO, "Spread"),
+            Palette::paint(
+                Palette::SUCCESS,
+                format!(
+                    "{} overdue",
+                    pluralize("card", overdue(&rows, now, opts.day).count())
+                )
+            ),
+            Palette::dim(format!("over {}", pluralize("day", days)))
+        );
+        apply(&mut rows, &spread);
+        changes.extend(spread);
+    }
+
+    if opts.dry_run {
+        println!("{}", Palette::dim("Dry run; nothing was changed."));
+        return Ok(());
+    }
+    // a card frozen and then spread only needs its final times
+    changes.reverse();
+    let mut seen = std::collections::HashSet::new();
+    changes.retain(|change| seen.insert(change.card_hash.clone()));
+    let changed = db.reschedule_cards(&changes).await?;
+    println!(
+        "{} {}",
+        Palette::paint(Palette::SUCCESS, "Rescheduled"),
+        Palette::paint(Palette::INFO, pluralize("card", changed as usize))
+    );
+    Ok(())
+}
+
+/// Push reviewed cards forward by however much of `from..until` falls after their last review,
+/// so the time away is not counted as forgetting. Cards that came due before `from` were yours
+/// to review before leaving, and are left alone. A card's last review never moves past `now`.
+pub fn freeze(
+    rows: &[CardStatsRow],
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Vec<Reschedule> {
+    rows.iter()
+        .filter(|row| !row.suspended && row.phase == "review")
+        .filter_map(|row| {
+            let (Some(last_reviewed_at), Some(due_date)) = (row.last_reviewed_at, row.due_date)
+            else {
+                return None;
+            };
+            if due_date < from {
+                return None;
+            }
+            let shift = until - last_reviewed_at.max(from);
+            if shift <= Duration::zero() {
+                return None;
+            }
+            Some(Reschedule {
+                card_hash: row.card_hash.clone(),
+                last_reviewed_at: (last_reviewed_at + shift).min(now),
+                due_date: due_date + shift,
+            })
+        })
+        .collect()
+}
+
+/// Spread cards that were due before today over `days` study days. The cards you are least
+/// likely to remember stay due today; the rest move to the start of a later day.
+pub fn spread_overdue(
+    rows: &[CardStatsRow],
+    days: usize,
+    decay: f32,
+    now: DateTime<Utc>,
+    day: DayBoundary,
+) -> Vec<Reschedule> {
+    let mut cards: Vec<(&CardStatsRow, f32)> = overdue(rows, now, day)
+        .map(|row| (row, row.retrievability(now, decay).unwrap_or_default()))
+        .collect();
+    cards.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    let total = cards.len();
+    let today = day.study_day(now);
+    cards
+        .into_iter()
+        .enumerate()
+        .filter_map(|(idx, (row, _))| {
+            let offset = (idx * days / total.max(1)) as i64;
+            if offset == 0 {
+                return None;
+            }
+            Some(Reschedule {
+                card_hash: row.card_hash.clone(),
+                last_reviewed_at: row.last_reviewed_at?,
+                due_date: day.start_of(today + Duration::days(offset)),
+            })
+        })
+        .collect()
+}
+
+fn overdue(
+    rows: &[CardStatsRow],
+    now: DateTime<Utc>,
+    day: DayBoundary,
+) -> impl Iterator<Item = &CardStatsRow> {
+    let start_of_today = day.start_of_today(now);
+    rows.iter().filter(move |row| {
+        !row.suspended
+            && row.phase == "review"
+            && row.last_reviewed_at.is_some()
+            && row.due_date.is_some_and(|due| due < start_of_today)
+    })
+}
+
+fn apply(rows: &mut [CardStatsRow], changes: &[Reschedule]) {
+    for change in changes {
+        if let Some(row) = rows
+            .iter_mut()
+            .find(|row| row.card_hash == change.card_hash)
+        {
+            row.last_reviewed_at = Some(change.last_reviewed_at);
+            row.due_date = Some(change.due_date);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fsrs::FSRS6_DEFAULT_DECAY;
+
+    fn row(
+        hash: &str,
+        stability: f64,
+        last_reviewed_at: DateTime<Utc>,
+        due: DateTime<Utc>,
+    ) -> CardStatsRow {
+        CardStatsRow {
+            card_hash: hash.into(),
+            review_count: 3,
+            due_date: Some(due),
+            interval_raw: Some(5.0),
+            difficulty: Some(5.0),
+            stability: Some(stability),
+            last_reviewed_at: Some(last_reviewed_at),
+            suspended: false,
+            buried_until: None,
+            phase: "review".into(),
+        }
+    }
+
+    #[test]
+    fn spread_keeps_the_weakest_cards_today() {
+        let now = Utc::now();
+        let day = DayBoundary::default();
+        let last = now - Duration::days(20);
+        let due = now - Duration::days(10);
+        let rows: Vec<CardStatsRow> = (0..6)
+            .map(|i| row(&format!("card-{i}"), 2.0 + i as f64 * 10.0, last, due))
+            .collect();
+
+        let changes = spread_overdue(&rows, 3, FSRS6_DEFAULT_DECAY, now, day);
+        // two cards per day; the two least stable stay due today
+        assert_eq!(changes.len(), 4);
+        assert!(changes.iter().all(|change| change.card_hash != "card-0"));
+        assert!(changes.iter().all(|change| change.card_hash != "card-1"));
+        let today = day.study_day(now);
+        let last_day = changes
+            .iter()
+            .map(|change| day.study_day(change.due_date))
+            .max();
+        assert_eq!(last_day, Some(today + Duration::days(2)));
+        assert_eq!(
+            changes
+                .iter()
+                .find(|change| change.card_hash == "card-5")
+                .map(|change| day.study_day(change.due_date)),
+            Some(today + Duration::days(2))
+        );
+    }
+
+    #[test]
+    fn freezing_skips_time_away() {
+        let now = Utc::now();
+        let from = now - Duration::days(14);
+        let until = now - Duration::days(1);
+        let mut rows = vec![
+            // due before leaving, so it should have been reviewed then
+            row(
+                "overdue",
+                5.0,
+                now - Duration::days(20),
+                now - Duration::days(15),
+            ),
+            // reviewed before leaving: the whole trip is skipped
+            row(
+                "before",
+                5.0,
+                now - Duration::days(20),
+                now - Duration::days(10),
+            ),
+            // reviewed during the trip: only the rest of it is skipped
+            row(
+                "during",
+                5.0,
+                now - Duration::days(4),
+                now - Duration::days(2),
+            ),
+            // reviewed after coming back
+            row(
+                "after",
+                5.0,
+                now - Duration::hours(2),
+                now + Duration::days(3),
+            ),
+        ];
+
+        let mut suspended = row(
+            "suspended",
+            5.0,
+            now - Duration::days(20),
+            now - Duration::days(10),
+        );
+        suspended.suspended = true;
+        let mut learning = row(
+            "learning",
+            5.0,
+            now - Duration::days(20),
+            now - Duration::days(10),
+        );
+        learning.phase = "learning".into();
+        rows.extend([suspended, learning]);
+
+        let changes = freeze(&rows, from, until, now);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].card_hash, "before");
+        assert_eq!(
+            changes[0].due_date,
+            now - Duration::days(10) + Duration::days(13)
+        );
+        assert_eq!(
+            changes[0].last_reviewed_at,
+            now - Duration::days(20) + Duration::days(13)
+        );
+        assert_eq!(changes[1].card_hash, "during");
+        assert_eq!(
+            changes[1].due_date,
+            now - Duration::days(2) + Duration::days(3)
+        );
+    }
+
+    #[test]
+    fn planned_absences_shift_cards_due_while_away() {
+        let now = Utc::now();
+        let from = now + Duration::days(5);
+        let until = now + Duration::days(12);
+        let rows = vec![
+            row(
+                "soon",
+                5.0,
+                now - Duration::days(2),
+                now + Duration::days(2),
+            ),
+            row(
+                "away",
+                5.0,
+                now - Duration::days(2),
+                now + Duration::days(7),
+            ),
+        ];
+
+        let changes = freeze(&rows, from, until, now);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].card_hash, "away");
+        assert_eq!(changes[0].due_date, now + Duration::days(14));
+        assert_eq!(changes[0].last_reviewed_at, now);
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::Utc;
+
+use crate::card::Card;
+use crate::crud::DB;
+use crate::notes::register_apple_notes_cards;
+use crate::palette::Palette;
+use crate::parser::register_all_cards;
+use crate::simulation::{SimulationOptions, WorkloadSimulation, simulate_workload};
+use crate::utils::pluralize;
+
+pub async fn run(
+    db: &DB,
+    paths: Vec<PathBuf>,
+    options: SimulationOptions,
+    apple_notes: bool,
+) -> Result<()> {
+    let simulation = simulate_collection(db, paths, &options, apple_notes).await?;
+    let Some(simulation) = simulation else {
+        println!("{}", Palette::dim("No cards found to simulate."));
+        return Ok(());
+    };
+    render_table(&simulation, &options);
+    Ok(())
+}
+
+async fn simulate_collection(
+    db: &DB,
+    paths: Vec<PathBuf>,
+    options: &SimulationOptions,
+    apple_notes: bool,
+) -> Result<Option<WorkloadSimulation>> {
+    let (card_hashes, _) = if apple_notes {
+        register_apple_notes_cards(db).await?
+    } else {
+        register_all_cards(db, paths).await?
+    };
+    simulate_cards(db, &card_hashes, options).await
+}
+
+/// Run the workload simulation for already registered cards.
+pub async fn simulate_cards(
+    db: &DB,
+    card_hashes: &HashMap<String, Card>,
+    options: &SimulationOptions,
+) -> Result<Option<WorkloadSimulation>> {
+    if card_hashes.is_empty() {
+        return Ok(None);
+    }
+    let rows = db.card_stats_rows(card_hashes).await?;
+    let review_log = db.review_log().await?;
+    let parameters = db.fsrs_parameters().await?;
+    simulate_workload(&rows, &review_log, &parameters, options, Utc::now()).map(Some)
+}
+
+fn render_table(simulation: &WorkloadSimulation, options: &SimulationOptions) {
+    println!(
+        "{} {} {}",
+        Palette::dim("Simulated"),
+        Palette::paint(Palette::INFO, pluralize("card", simulation.num_cards)),
+        Palette::dim(format!(
+            "over {} with up to {} per day",
+            pluralize("day", simulation.days),
+            pluralize("new card", options.new_cards_per_day)
+        ))
+    );
+    println!();
+    println!(
+        "{}",
+        Palette::paint(
+            Palette::ACCENT,
+            format!(
+                "{:<10} {:>12} {:>12} {:>12}",
+                "Retention", "Minutes/day", "Reviews/day", "Remembered"
+            )
+        )
+    );
+    for (idx, outcome) in simulation.outcomes.iter().enumerate() {
+        let row = format!(
+            "{:<10} {:>12.1} {:>12.1} {:>12.0}",
+            format!("{:.0}%", outcome.retention * 100.0),
+            outcome.minutes_per_day,
+            outcome.reviews_per_day,
+            outcome.memorized
+        );
+        if idx == simulation.recommended {
+            println!(
+                "{}  {}",
+                Palette::paint(Palette::SUCCESS, row),
+                Palette::dim("← recommended")
+            );
+        } else {
+            println!("{row}");
+        }
+    }
+    println!();
+
+    let best = simulation.recommended_outcome();
+    let retention = format!("--retention {:.2}", best.retention);
+    match options.minutes_per_day {
+        Some(budget) if !simulation.within_budget => println!(
+            "{} {} {}",
+            Palette::paint(
+                Palette::WARNING,
+                format!("No target fits in {budget:.0} minutes per day."),
+            ),
+            Palette::dim("The cheapest option is"),
+            Palette::paint(Palette::INFO, retention)
+        ),
+        Some(budget) => println!(
+            "{} {} {}",
+            Palette::dim(format!(
+                "Remembers the most within {budget:.0} minutes per day:"
+            )),
+            Palette::paint(Palette::SUCCESS, retention),
+            Palette::dim(format!("(~{:.1} min/day)", best.minutes_per_day))
+        ),
+        None => println!(
+            "{} {} {}",
+            Palette::dim("Best knowledge per minute studied:"),
+            Palette::paint(Palette::SUCCESS, retention),
+            Palette::dim("(pass --minutes to fit a daily time budget)")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn simulates_registered_cards() {
+        let db = DB::new_in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("deck.md"),
+            "Q: one?\nA: 1\n\nQ: two?\nA: 2\n\nC: [three] is three",
+        )
+        .unwrap();
+
+        let options = SimulationOptions {
+            days: 30,
+            ..Default::default()
+        };
+        let simulation = simulate_collection(&db, vec![dir.path().into()], &options, false)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(simulation.num_cards, 3);
+        assert!(simulation.outcomes.iter().all(|o| o.minutes_per_day > 0.0));
+    }
+
+    #[tokio::test]
+    async fn empty_collection_has_nothing_to_simulate() {
+        let db = DB::new_in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let simulation = simulate_collection(
+            &db,
+            vec![dir.path().into()],
+            &SimulationOptions::default(),
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(simulation.is_none());
+    }
+}
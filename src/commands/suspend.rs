@@ -0,0 +1,252 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::card::Card;
+use crate::crud::DB;
+use crate::palette::Palette;
+use crate::parser::register_all_cards;
+use crate::utils::pluralize;
+
+/// Suspend or unsuspend the card `query` names exactly, or else the cards it matches.
+/// Matches at more than one place change nothing unless `all` is set; siblings of a
+/// multi-cloze or reversible card share a place and count as one card.
+pub async fn run(
+    db: &DB,
+    query: &str,
+    paths: Vec<PathBuf>,
+    suspend: bool,
+    all: bool,
+) -> Result<()> {
+    let cards = queried_cards(db, query, paths).await?;
+    if cards.is_empty() {
+        println!(
+            "{}",
+            Palette::paint(Palette::WARNING, format!("No cards match '{query}'."))
+        );
+        return Ok(());
+    }
+
+    let mut places: Vec<String> = cards.iter().map(Card::location).collect();
+    places.dedup();
+    for place in &places {
+        println!("  {}", Palette::dim(place));
+    }
+    let (command, verb) = if suspend {
+        ("suspend", "Suspended")
+    } else {
+        ("unsuspend", "Unsuspended")
+    };
+    if places.len() > 1 && !all {
+        println!(
+            "{} {}",
+            Palette::paint(
+                Palette::WARNING,
+                format!("'{query}' matches {}.", pluralize("card", places.len()))
+            ),
+            Palette::dim(format!(
+                "Pass --all to {command} them all, or name one card by path:line or id."
+            ))
+        );
+        return Ok(());
+    }
+
+    let changed = if suspend {
+        db.suspend_cards(&cards).await?
+    } else {
+        db.unsuspend_cards(&cards).await?
+    };
+    println!(
+        "{} {} {}",
+        Palette::paint(Palette::SUCCESS, verb),
+        Palette::paint(Palette::INFO, pluralize("card", changed as usize)),
+        Palette::dim(format!("({} matched)", cards.len()))
+    );
+    Ok(())
+}
+
+/// Registered cards under `paths` that match `query`, in file order.
+pub async fn matching_cards(db: &DB, query: &str, paths: Vec<PathBuf>) -> Result<Vec<Card>> {
+    cards_where(db, paths, |card| card.matches_query(query)).await
+}
+
+/// Registered cards under `paths` that `query` names exactly, by `path:line` or by full
+/// hash or id, in file order.
+pub async fn named_cards(db: &DB, query: &str, paths: Vec<PathBuf>) -> Result<Vec<Card>> {
+    cards_where(db, paths, |card| card.is_named_by(query)).await
+}
+
+/// The cards `query` names exactly, if any, or else the cards it matches, in file order.
+pub async fn queried_cards(db: &DB, query: &str, paths: Vec<PathBuf>) -> Result<Vec<Card>> {
+    let cards = cards_where(db, paths, |_| true).await?;
+    if cards.iter().any(|card| card.is_named_by(query)) {
+        return Ok(cards
+            .into_iter()
+            .filter(|card| card.is_named_by(query))
+            .collect());
+    }
+    Ok(cards
+        .into_iter()
+        .filter(|card| card.matches_query(query))
+        .collect())
+}
+
+async fn cards_where(
+    db: &DB,
+    paths: Vec<PathBuf>,
+    keep: impl Fn(&Card) -> bool,
+) -> Result<Vec<Card>> {
+    let (card_hashes, _) = register_all_cards(db, paths).await?;
+    let mut cards: Vec<Card> = card_hashes
+        .into_values()
+        .filter(|card| keep(card))
+        .collect();
+    cards.sort_by(|a, b| {
+        a.file_path
+            .cmp(&b.file_path)
+            .then(a.file_card_range.cmp(&b.file_card_range))
+    });
+    Ok(cards)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::crud::limits::SessionLimits;
+    use crate::utils::DayBoundary;
+
+    #[tokio::test]
+    async fn suspend_and_unsuspend_matching_cards() {
+        let db = DB::new_in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("deck.md"),
+            "Q: Capital of Japan?\nA: Tokyo\n\nQ: Capital of France?\nA: Paris",
+        )
+        .unwrap();
+        let paths = vec![dir.path().to_path_buf()];
+
+        run(&db, "tokyo", paths.clone(), true, false).await.unwrap();
+        let (card_hashes, _) = register_all_cards(&db, paths.clone()).await.unwrap();
+        let due = db
+            .due_today(
+                &card_hashes,
+                SessionLimits::default(),
+                DayBoundary::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(due.len(), 1);
+        assert!(due[0].matches_query("paris"));
+
+        let cards = matching_cards(&db, "capital", paths.clone()).await.unwrap();
+        assert_eq!(cards.len(), 2);
+        assert_eq!(db.suspend_cards(&cards).await.unwrap(), 1);
+        assert!(
+            db.due_today(
+                &card_hashes,
+                SessionLimits::default(),
+                DayBoundary::default()
+            )
+            .await
+            .unwrap()
+            .is_empty()
+        );
+
+        // a loose query matching several cards needs --all
+        run(&db, "capital", paths.clone(), false, false)
+            .await
+            .unwrap();
+        assert!(
+            db.due_today(
+                &card_hashes,
+                SessionLimits::default(),
+                DayBoundary::default()
+            )
+            .await
+            .unwrap()
+            .is_empty()
+        );
+
+        run(&db, "capital", paths, false, true).await.unwrap();
+        let due = db
+            .due_today(
+                &card_hashes,
+                SessionLimits::default(),
+                DayBoundary::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(due.len(), 2);
+
+        let stats = db
+            .collection_stats(&card_hashes, DayBoundary::default())
+            .await
+            .unwrap();
+        assert_eq!(stats.suspended_cards, 0);
+    }
+
+    #[tokio::test]
+    async fn siblings_are_named_by_their_shared_line() {
+        let db = DB::new_in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("deck.md"),
+            "C: [Tokyo] is the capital of [Japan]\n\nR: Capital of France?\nA: Paris\n",
+        )
+        .unwrap();
+        let paths = vec![dir.path().to_path_buf()];
+
+        run(&db, "deck.md:1", paths.clone(), true, false)
+            .await
+            .unwrap();
+        let (card_hashes, _) = register_all_cards(&db, paths.clone()).await.unwrap();
+        let stats = db
+            .collection_stats(&card_hashes, DayBoundary::default())
+            .await
+            .unwrap();
+        assert_eq!(stats.suspended_cards, 2);
+
+        run(&db, "deck.md:3", paths.clone(), true, false)
+            .await
+            .unwrap();
+        let stats = db
+            .collection_stats(&card_hashes, DayBoundary::default())
+            .await
+            .unwrap();
+        assert_eq!(stats.suspended_cards, 4);
+
+        // cards at different places still need --all
+        run(&db, "capital", paths, false, false).await.unwrap();
+        let stats = db
+            .collection_stats(&card_hashes, DayBoundary::default())
+            .await
+            .unwrap();
+        assert_eq!(stats.suspended_cards, 4);
+    }
+
+    #[tokio::test]
+    async fn exact_names_win_over_loose_matches() {
+        let db = DB::new_in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let mut deck = String::new();
+        for n in 0..6 {
+            deck.push_str(&format!("Q: Question {n}?\nA: Answer {n}\n\n"));
+        }
+        fs::write(dir.path().join("deck.md"), deck).unwrap();
+        let paths = vec![dir.path().to_path_buf()];
+
+        // `deck.md:1` is also found in `deck.md:10` and `deck.md:13`
+        let cards = queried_cards(&db, "deck.md:1", paths.clone())
+            .await
+            .unwrap();
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].file_card_range.0, 0);
+        assert_eq!(
+            queried_cards(&db, "deck.md:", paths).await.unwrap().len(),
+            6
+        );
+    }
+}
@@ -0,0 +1,400 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::Duration;
+use serde::Deserialize;
+
+use crate::crud::leeches::{LeechAction, LeechPolicy};
+use crate::crud::limits::DailyLimits;
+use crate::fsrs::{IntervalLimits, LearningSteps, validate_retention};
+use crate::order::{DEFAULT_NEW_CARD_SPACING, DrillOrder};
+use crate::utils::{DayBoundary, get_data_dir};
+
+const CONFIG_FILE_NAME: &str = "repeater.toml";
+const DEFAULT_RETENTION: f32 = 0.9;
+
+/// User settings read from `repeater.toml` in the data directory, with any keys
+/// from a `repeater.toml` next to the cards taking precedence.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// Default target retention when `drill --retention` is not given.
+    pub retention: f32,
+    pub steps: LearningSteps,
+    pub intervals: IntervalLimits,
+    pub leech: LeechPolicy,
+    pub day: DayBoundary,
+    pub daily_limits: DailyLimits,
+    pub fuzz: bool,
+    pub load_balance: bool,
+    pub drill_order: DrillOrder,
+    pub new_card_spacing: usize,
+}
+
+/// The file as written by the user; every key is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    retention: Option<f32>,
+    maximum_interval: Option<u32>,
+    minimum_lapse_interval: Option<u32>,
+    learning_steps: Option<Vec<String>>,
+    relearning_steps: Option<Vec<String>>,
+    leech_threshold: Option<u32>,
+    leech_action: Option<LeechAction>,
+    day_rollover_hour: Option<u32>,
+    new_cards_per_day: Option<usize>,
+    reviews_per_day: Option<usize>,
+    fuzz: Option<bool>,
+    load_balance: Option<bool>,
+    drill_order: Option<DrillOrder>,
+    new_card_spacing: Option<usize>,
+}
+
+impl ConfigFile {
+    /// `None` when the file does not exist.
+    fn read(path: &Path) -> Result<Option<Self>> {
+        match fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map(Some)
+                .with_context(|| format!("Failed to parse config file at {}", path.display())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err)
+                .with_context(|| format!("Failed to read config file at {}", path.display())),
+        }
+    }
+
+    /// Keys set in `other` win over the ones set here.
+    fn overlay(self, other: ConfigFile) -> ConfigFile {
+        ConfigFile {
+            retention: other.retention.or(self.retention),
+            maximum_interval: other.maximum_interval.or(self.maximum_interval),
+            minimum_lapse_interval: other.minimum_lapse_interval.or(self.minimum_lapse_interval),
+            learning_steps: other.learning_steps.or(self.learning_steps),
+            relearning_steps: other.relearning_steps.or(self.relearning_steps),
+            leech_threshold: other.leech_threshold.or(self.leech_threshold),
+            leech_action: other.leech_action.or(self.leech_action),
+            day_rollover_hour: other.day_rollover_hour.or(self.day_rollover_hour),
+            new_cards_per_day: other.new_cards_per_day.or(self.new_cards_per_day),
+            reviews_per_day: other.reviews_per_day.or(self.reviews_per_day),
+            fuzz: other.fuzz.or(self.fuzz),
+            load_balance: other.load_balance.or(self.load_balance),
+            drill_order: other.drill_order.or(self.drill_order),
+            new_card_spacing: other.new_card_spacing.or(self.new_card_spacing),
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Self> {
+        Self::load_from(&config_file_path()?)
+    }
+
+    /// The global config, overridden by the nearest `repeater.toml` at or above the
+    /// first of `paths`.
+    pub fn load_for(paths: &[PathBuf]) -> Result<Self> {
+        Self::load_layered(&config_file_path()?, paths.first().map(PathBuf::as_path))
+    }
+
+    fn load_layered(global_path: &Path, cards_path: Option<&Path>) -> Result<Self> {
+        let global = ConfigFile::read(global_path)?.unwrap_or_default();
+        let file = match cards_path.and_then(|path| find_override(path, global_path)) {
+            Some(path) => match ConfigFile::read(&path)? {
+                Some(local) => global.overlay(local),
+                None => global,
+            },
+            None => global,
+        };
+        Self::from_file(file)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let file = ConfigFile::read(path)?.unwrap_or_default();
+        Self::from_file(file).with_context(|| format!("Invalid config file at {}", path.display()))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        Self::from_file(toml::from_str(contents)?)
+    }
+
+    fn from_file(file: ConfigFile) -> Result<Self> {
+        let retention = file.retention.unwrap_or(DEFAULT_RETENTION);
+        validate_retention(retention).context("Invalid retention")?;
+        let default_intervals = IntervalLimits::default();
+        let intervals = IntervalLimits::new(
+            file.maximum_interval
+                .map_or(default_intervals.maximum, f64::from),
+            file.minimum_lapse_interval
+                .map_or(default_intervals.minimum_after_lapse, f64::from),
+        )?;
+        let defaults = LearningSteps::default();
+        let steps = LearningSteps {
+            learning: match file.learning_steps {
+                Some(steps) => parse_steps("learning_steps", &steps)?,
+                None => defaults.learning,
+            },
+            relearning: match file.relearning_steps {
+                Some(steps) => parse_steps("relearning_steps", &steps)?,
+                None => defaults.relearning,
+            },
+        };
+        let mut leech = LeechPolicy::default();
+        if let Some(threshold) = file.leech_threshold {
+            if threshold == 0 {
+                bail!("leech_threshold must be at least 1");
+            }
+            leech.threshold = threshold;
+        }
+        if let Some(action) = file.leech_action {
+            leech.action = action;
+        }
+        let day = match file.day_rollover_hour {
+            Some(hour) => DayBoundary::new(hour)?,
+            None => DayBoundary::default(),
+        };
+        if file.new_card_spacing == Some(0) {
+            bail!("new_card_spacing must be at least 1");
+        }
+        let daily_limits = DailyLimits {
+            new_cards: file.new_cards_per_day,
+            reviews: file.reviews_per_day,
+        };
+        Ok(Self {
+            retention,
+            steps,
+            intervals,
+            leech,
+            day,
+            daily_limits,
+            fuzz: file.fuzz.unwrap_or(true),
+            load_balance: file.load_balance.unwrap_or(false),
+            drill_order: file.drill_order.unwrap_or_default(),
+            new_card_spacing: file.new_card_spacing.unwrap_or(DEFAULT_NEW_CARD_SPACING),
+        })
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            retention: DEFAULT_RETENTION,
+            steps: LearningSteps::default(),
+            intervals: IntervalLimits::default(),
+            leech: LeechPolicy::default(),
+            day: DayBoundary::default(),
+            daily_limits: DailyLimits::default(),
+            fuzz: true,
+            load_balance: false,
+            drill_order: DrillOrder::default(),
+            new_card_spacing: DEFAULT_NEW_CARD_SPACING,
+        }
+    }
+}
+
+pub fn config_file_path() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join(CONFIG_FILE_NAME))
+}
+
+/// The closest `repeater.toml` in `path`'s directory or one of its parents,
+/// other than the global one.
+fn find_override(path: &Path, global_path: &Path) -> Option<PathBuf> {
+    let path = fs::canonicalize(path).ok()?;
+    let global_path = fs::canonicalize(global_path).ok();
+    let dir = if path.is_dir() { &path } else { path.parent()? };
+    dir.ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|candidate| candidate.is_file() && Some(candidate) != global_path.as_ref())
+}
+
+fn parse_steps(key: &str, steps: &[String]) -> Result<Vec<Duration>> {
+    steps
+        .iter()
+        .map(|step| parse_step(step).with_context(|| format!("Invalid step in {key}")))
+        .collect()
+}
+
+/// Parse a delay such as `30s`, `10m`, `2h`, or `1d`.
+pub fn parse_step(step: &str) -> Result<Duration> {
+    let step = step.trim();
+    let split = step
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| anyhow!("'{step}' is missing a unit (s, m, h, or d)"))?;
+    let (amount, unit) = step.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| anyhow!("'{step}' should start with a whole number"))?;
+    if amount == 0 {
+        bail!("'{step}' must be longer than zero");
+    }
+    match unit {
+        "s" => Ok(Duration::seconds(amount)),
+        "m" => Ok(Duration::minutes(amount)),
+        "h" => Ok(Duration::hours(amount)),
+        "d" => Ok(Duration::days(amount)),
+        _ => bail!("'{step}' has an unknown unit; use s, m, h, or d"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn missing_file_uses_defaults() {
+        let dir = tempdir().unwrap();
+        let config = Config::load_from(&dir.path().join(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn reads_steps() {
+        let config = Config::parse(
+            r#"
+            learning_steps = ["15m", "1h", "4h", "1d"]
+            relearning_steps = []
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.steps.learning,
+            vec![
+                Duration::minutes(15),
+                Duration::hours(1),
+                Duration::hours(4),
+                Duration::days(1)
+            ]
+        );
+        assert!(config.steps.relearning.is_empty());
+
+        let config = Config::parse(r#"relearning_steps = ["30s"]"#).unwrap();
+        assert_eq!(config.steps.learning, LearningSteps::default().learning);
+        assert_eq!(config.steps.relearning, vec![Duration::seconds(30)]);
+    }
+
+    #[test]
+    fn rejects_bad_steps() {
+        assert!(parse_step("10").is_err());
+        assert!(parse_step("m").is_err());
+        assert!(parse_step("0m").is_err());
+        assert!(parse_step("5w").is_err());
+        assert!(Config::parse(r#"learning_step = ["1m"]"#).is_err());
+    }
+
+    #[test]
+    fn reads_leech_policy() {
+        let config = Config::parse(
+            r#"
+            leech_threshold = 4
+            leech_action = "suspend"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.leech,
+            LeechPolicy {
+                threshold: 4,
+                action: LeechAction::Suspend
+            }
+        );
+
+        assert!(Config::parse("leech_threshold = 0").is_err());
+        assert!(Config::parse(r#"leech_action = "delete""#).is_err());
+    }
+
+    #[test]
+    fn reads_day_rollover_hour() {
+        let config = Config::parse("day_rollover_hour = 0").unwrap();
+        assert_eq!(config.day.rollover_hour, 0);
+        assert_eq!(Config::default().day, DayBoundary::default());
+        assert!(Config::parse("day_rollover_hour = 24").is_err());
+    }
+
+    #[test]
+    fn reads_daily_limits() {
+        let config = Config::parse("new_cards_per_day = 10").unwrap();
+        assert_eq!(
+            config.daily_limits,
+            DailyLimits {
+                new_cards: Some(10),
+                reviews: None
+            }
+        );
+        assert_eq!(Config::default().daily_limits, DailyLimits::default());
+    }
+
+    #[test]
+    fn reads_fuzz_switches() {
+        let config = Config::parse("").unwrap();
+        assert!(config.fuzz);
+        assert!(!config.load_balance);
+
+        let config = Config::parse("fuzz = false\nload_balance = true").unwrap();
+        assert!(!config.fuzz);
+        assert!(config.load_balance);
+    }
+
+    #[test]
+    fn reads_scheduling_defaults() {
+        let config = Config::parse(
+            r#"
+            retention = 0.85
+            maximum_interval = 180
+            minimum_lapse_interval = 2
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.retention, 0.85);
+        assert_eq!(config.intervals, IntervalLimits::new(180.0, 2.0).unwrap());
+
+        assert!(Config::parse("retention = 0.5").is_err());
+        assert!(Config::parse("maximum_interval = 0").is_err());
+        assert!(Config::parse("maximum_interval = 5\nminimum_lapse_interval = 10").is_err());
+    }
+
+    #[test]
+    fn directory_config_overrides_the_global_one() {
+        let data_dir = tempdir().unwrap();
+        let global_path = data_dir.path().join(CONFIG_FILE_NAME);
+        fs::write(&global_path, "retention = 0.8\nmaximum_interval = 365").unwrap();
+
+        let decks = tempdir().unwrap();
+        let nested = decks.path().join("spanish").join("verbs");
+        fs::create_dir_all(&nested).unwrap();
+        let card_file = nested.join("cards.md");
+        fs::write(&card_file, "Q: hola?\nA: hello").unwrap();
+
+        let config = Config::load_layered(&global_path, Some(&card_file)).unwrap();
+        assert_eq!(config.retention, 0.8);
+
+        fs::write(decks.path().join(CONFIG_FILE_NAME), "retention = 0.95").unwrap();
+        for path in [&card_file, &nested, &decks.path().to_path_buf()] {
+            let config = Config::load_layered(&global_path, Some(path)).unwrap();
+            assert_eq!(config.retention, 0.95);
+            assert_eq!(config.intervals.maximum, 365.0);
+        }
+
+        let config = Config::load_layered(&global_path, None).unwrap();
+        assert_eq!(config.retention, 0.8);
+    }
+
+    #[test]
+    fn reads_drill_order() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.drill_order, DrillOrder::Random);
+
+        let config = Config::parse(
+            r#"
+            drill_order = "interleave-new"
+            new_card_spacing = 3
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.drill_order, DrillOrder::InterleaveNew);
+        assert_eq!(config.new_card_spacing, 3);
+
+        assert!(Config::parse(r#"drill_order = "sideways""#).is_err());
+        assert!(Config::parse("new_card_spacing = 0").is_err());
+    }
+}
@@ -1,7 +1,10 @@
 use anyhow::Result;
 use futures::TryStreamExt;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
 
 use anyhow::anyhow;
 
@@ -9,39 +12,93 @@ use crate::card::Card;
 
 use crate::fsrs::ReviewStatus;
 use crate::fsrs::ReviewedPerformance;
-use crate::fsrs::update_performance;
-use crate::fsrs::{LEARN_AHEAD_THRESHOLD_MINS, Performance};
+use crate::fsrs::{IntervalLimits, LEARN_AHEAD_THRESHOLD_MINS, LearningSteps, Performance, Phase};
+use crate::fsrs::{is_lapse, update_performance};
+use crate::fuzz::{DueLoad, fuzz_performance, fuzz_seed};
+use crate::utils::DayBoundary;
 
 use super::DB;
+use super::leeches::{LeechAction, LeechPolicy};
+use super::limits::SessionLimits;
+use super::revlog::{NewReviewLog, ReviewKind};
 
-impl DB {
-    pub async fn add_card(&self, card: &Card) -> Result<()> {
-        let now = chrono::Utc::now().to_rfc3339();
+/// Everything besides the card's history that decides how a review is recorded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReviewOptions {
+    pub retention: f32,
+    pub steps: LearningSteps,
+    pub intervals: IntervalLimits,
+    pub leech: LeechPolicy,
+    /// Spread review intervals over a few days so cards reviewed together don't stay together.
+    pub fuzz: bool,
+    /// Upcoming due counts to steer fuzzed intervals towards lighter days.
+    pub load: Option<DueLoad>,
+}
 
-        sqlx::query!(
-            r#"
-        INSERT or ignore INTO cards (
-            card_hash,
-            added_at,
-            last_reviewed_at,
-            stability,
-            difficulty,
-            interval_raw,
-            interval_days,
-            due_date,
-            review_count
-        )
-        VALUES (?, ?, NULL, NULL, NULL, NULL, 0, NULL, 0)
-        "#,
-            card.card_hash,
-            now
-        )
-        .execute(&self.pool)
-        .await?;
+impl Default for ReviewOptions {
+    fn default() -> Self {
+        Self {
+            retention: 0.9,
+            steps: LearningSteps::default(),
+            intervals: IntervalLimits::default(),
+            leech: LeechPolicy::default(),
+            fuzz: true,
+            load: None,
+        }
+    }
+}
 
-        Ok(())
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReviewOutcome {
+    pub interval_raw: f64,
+    pub due_date: DateTime<Utc>,
+    pub lapses: i64,
+    pub leech: bool,
+    /// The card just became a leech and was suspended because of it.
+    pub auto_suspended: bool,
+    pub undo: ReviewUndo,
+}
+
+/// What a review overwrote, so that [`DB::undo_review`] can put it back.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReviewUndo {
+    pub previous: Performance,
+    pub lapses: i64,
+    pub leech: bool,
+    pub suspended: bool,
+    pub revlog_id: i64,
+}
+
+/// New review times for a card, set without recording a review.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reschedule {
+    pub card_hash: String,
+    pub last_reviewed_at: DateTime<Utc>,
+    pub due_date: DateTime<Utc>,
+}
+
+/// Where a card was last seen, and what it said then.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoredCard {
+    pub card_hash: String,
+    pub file_line: i64,
+    pub content: String,
+}
+
+/// Paths are stored canonicalized, so the same file matches however it was passed in.
+fn stored_path(path: &Path) -> String {
+    std::fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+impl DB {
+    pub async fn add_card(&self, card: &Card) -> Result<()> {
+        self.add_cards_batch(std::slice::from_ref(card)).await
     }
 
+    /// Register cards, and record where each was seen and its text for [`DB::cards_in_file`].
     pub async fn add_cards_batch(&self, cards: &[Card]) -> Result<()> {
         let mut tx = self.pool.begin().await?;
 
@@ -49,9 +106,13 @@ impl DB {
 
         for card in cards {
             let added_at = now.clone();
+            let file_path = stored_path(&card.file_path);
+            let file_line = card.file_card_range.0 as i64;
+            let content = card.comparable_text();
+            let tags = card.tags.join(" ");
             sqlx::query!(
                 r#"
-            INSERT or ignore INTO cards (
+            INSERT INTO cards (
                 card_hash,
                 added_at,
                 last_reviewed_at,
@@ -60,12 +121,25 @@ impl DB {
                 interval_raw,
                 interval_days,
                 due_date,
-                review_count
+                review_count,
+                file_path,
+                file_line,
+                content,
+                tags
             )
-            VALUES (?, ?, NULL, NULL, NULL, NULL, 0, NULL, 0)
+            VALUES (?, ?, NULL, NULL, NULL, NULL, 0, NULL, 0, ?, ?, ?, ?)
+            ON CONFLICT(card_hash) DO UPDATE SET
+                file_path = excluded.file_path,
+                file_line = excluded.file_line,
+                content = excluded.content,
+                tags = excluded.tags
             "#,
                 card.card_hash,
-                added_at
+                added_at,
+                file_path,
+                file_line,
+                content,
+                tags
             )
             .execute(&mut *tx)
             .await?;
@@ -75,6 +149,42 @@ impl DB {
         Ok(())
     }
 
+    /// Cards last seen in `file_path`, as recorded by [`DB::add_cards_batch`].
+    pub async fn cards_in_file(&self, file_path: &Path) -> Result<Vec<StoredCard>> {
+        let file_path = stored_path(file_path);
+        let rows = sqlx::query_as!(
+            StoredCard,
+            r#"
+            SELECT
+                card_hash as "card_hash!",
+                file_line as "file_line!: i64",
+                content as "content!"
+            FROM cards
+            WHERE file_path = ? AND file_line IS NOT NULL AND content IS NOT NULL
+            "#,
+            file_path
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Forget where these cards were last seen, so they stop being candidates for
+    /// [`carry_over_edits`](crate::parser::edits::carry_over_edits).
+    pub async fn clear_card_locations(&self, card_hashes: &[String]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for card_hash in card_hashes {
+            sqlx::query!(
+                "UPDATE cards SET file_path = NULL, file_line = NULL WHERE card_hash = ?",
+                card_hash
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
     pub async fn card_exists(&self, card: &Card) -> Result<bool> {
         let count: i64 = sqlx::query_scalar!(
             r#"SELECT COUNT(1) as "count!: i64" FROM cards WHERE card_hash = ?"#,
@@ -90,19 +200,80 @@ impl DB {
         card: &Card,
         review_status: ReviewStatus,
         optional_now: Option<chrono::DateTime<chrono::Utc>>,
-        retention: f32,
-    ) -> Result<f64> {
+        options: &ReviewOptions,
+        time_spent: std::time::Duration,
+    ) -> Result<ReviewOutcome> {
         let current_performance = self.get_card_performance(card).await?;
         let now = match optional_now {
             Some(now) => now,
             None => chrono::Utc::now(),
         };
 
-        let new_performance =
-            update_performance(current_performance, review_status, now, retention)?;
+        let parameters = self.fsrs_parameters().await?;
+        let mut new_performance = update_performance(
+            current_performance,
+            review_status,
+            now,
+            options.retention,
+            &parameters,
+            &options.steps,
+            &options.intervals,
+        )?;
+        if options.fuzz {
+            let seed = fuzz_seed(&card.card_hash, new_performance.review_count);
+            let after_lapse = match current_performance {
+                Performance::New => false,
+                Performance::Reviewed(previous) => is_lapse(previous.phase, review_status),
+            };
+            fuzz_performance(
+                &mut new_performance,
+                seed,
+                &options.intervals,
+                after_lapse,
+                options.load.as_ref(),
+            );
+        }
+
+        let (elapsed_days, previous_stability, previous_difficulty) = match current_performance {
+            Performance::New => (0.0, None, None),
+            Performance::Reviewed(previous) => (
+                now.signed_duration_since(previous.last_reviewed_at)
+                    .num_seconds()
+                    .max(0) as f64
+                    / 86_400.0,
+                Some(previous.stability),
+                Some(previous.difficulty),
+            ),
+        };
 
         let interval_days = new_performance.interval_days as i64;
         let review_count = new_performance.review_count as i64;
+        let phase = new_performance.phase.as_str();
+        let step = new_performance.phase.step() as i64;
+
+        let mut tx = self.pool.begin().await?;
+
+        // a lapse is a failed review right after a passing one
+        let previous_grade = Self::last_review_grade(&mut tx, &card.card_hash).await?;
+        let lapsed =
+            review_status.is_fail() && previous_grade.is_some_and(|grade| !grade.is_fail());
+        let current = sqlx::query!(
+            r#"
+            SELECT
+                lapses as "lapses!: i64",
+                leech as "leech!: bool",
+                suspended as "suspended!: bool"
+            FROM cards
+            WHERE card_hash = ?
+            "#,
+            card.card_hash
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let lapses = current.lapses + i64::from(lapsed);
+        let became_leech = lapsed && !current.leech && lapses >= i64::from(options.leech.threshold);
+        let leech = current.leech || became_leech;
+        let auto_suspended = became_leech && options.leech.action == LeechAction::Suspend;
 
         sqlx::query!(
             r#"
@@ -114,7 +285,12 @@ impl DB {
                 interval_raw = ?,
                 interval_days = ?,
                 due_date = ?,
-                review_count = ?
+                review_count = ?,
+                phase = ?,
+                step = ?,
+                lapses = ?,
+                leech = ?,
+                suspended = MAX(suspended, ?)
             WHERE card_hash = ?
             "#,
             new_performance.last_reviewed_at,
@@ -124,12 +300,113 @@ impl DB {
             interval_days,
             new_performance.due_date,
             review_count,
+            phase,
+            step,
+            lapses,
+            leech,
+            auto_suspended,
             card.card_hash,
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
+        .await?;
+
+        let revlog_id = Self::insert_review_log(
+            &mut tx,
+            NewReviewLog {
+                kind: ReviewKind::Review,
+                card_hash: &card.card_hash,
+                reviewed_at: now,
+                grade: review_status,
+                elapsed_days,
+                previous_stability,
+                previous_difficulty,
+                stability: new_performance.stability,
+                difficulty: new_performance.difficulty,
+                interval_raw: new_performance.interval_raw,
+                time_spent,
+            },
+        )
         .await?;
 
-        Ok(new_performance.interval_raw)
+        tx.commit().await?;
+
+        Ok(ReviewOutcome {
+            interval_raw: new_performance.interval_raw,
+            due_date: new_performance.due_date,
+            lapses,
+            leech,
+            auto_suspended,
+            undo: ReviewUndo {
+                previous: current_performance,
+                lapses: current.lapses,
+                leech: current.leech,
+                suspended: current.suspended,
+                revlog_id,
+            },
+        })
+    }
+
+    /// Take back a review: restore the card's state from before it and drop its log entry.
+    pub async fn undo_review(&self, card: &Card, undo: &ReviewUndo) -> Result<()> {
+        let previous = match undo.previous {
+            Performance::New => None,
+            Performance::Reviewed(previous) => Some(previous),
+        };
+        let last_reviewed_at = previous.map(|p| p.last_reviewed_at);
+        let stability = previous.map(|p| p.stability);
+        let difficulty = previous.map(|p| p.difficulty);
+        let interval_raw = previous.map(|p| p.interval_raw);
+        let interval_days = previous.map_or(0, |p| p.interval_days as i64);
+        let due_date = previous.map(|p| p.due_date);
+        let review_count = previous.map_or(0, |p| p.review_count as i64);
+        let phase = previous.map_or(Phase::Learning { step: 0 }, |p| p.phase);
+        let phase_name = phase.as_str();
+        let step = phase.step() as i64;
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            UPDATE cards
+            SET
+                last_reviewed_at = ?,
+                stability = ?,
+                difficulty = ?,
+                interval_raw = ?,
+                interval_days = ?,
+                due_date = ?,
+                review_count = ?,
+                phase = ?,
+                step = ?,
+                lapses = ?,
+                leech = ?,
+                suspended = ?
+            WHERE card_hash = ?
+            "#,
+            last_reviewed_at,
+            stability,
+            difficulty,
+            interval_raw,
+            interval_days,
+            due_date,
+            review_count,
+            phase_name,
+            step,
+            undo.lapses,
+            undo.leech,
+            undo.suspended,
+            card.card_hash,
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM revlog WHERE id = ? AND card_hash = ?",
+            undo.revlog_id,
+            card.card_hash
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
     }
 
     pub async fn get_card_performance(&self, card: &Card) -> Result<Performance> {
@@ -142,7 +419,9 @@ impl DB {
                 interval_raw as "interval_raw?: f64",
                 interval_days as "interval_days?: i64",
                 due_date as "due_date?: chrono::DateTime<chrono::Utc>",
-                review_count as "review_count!: i64"
+                review_count as "review_count!: i64",
+                phase,
+                step as "step!: i64"
             FROM cards
             WHERE card_hash = ?
             "#,
@@ -176,6 +455,9 @@ impl DB {
                 .due_date
                 .ok_or_else(|| anyhow!("missing due_date for card {}", card.card_hash))?,
             review_count: review_count as usize,
+            phase: Phase::from_parts(&row.phase, row.step.max(0) as usize).ok_or_else(|| {
+                anyhow!("invalid phase {} for card {}", row.phase, card.card_hash)
+            })?,
         };
 
         Ok(Performance::Reviewed(reviewed))
@@ -184,61 +466,290 @@ impl DB {
     pub async fn due_today(
         &self,
         card_hashes: &HashMap<String, Card>,
-        card_limit: Option<usize>,
-        new_card_limit: Option<usize>,
+        limits: SessionLimits,
+        day: DayBoundary,
     ) -> Result<Vec<Card>> {
-        let now = (chrono::Utc::now() + LEARN_AHEAD_THRESHOLD_MINS).to_rfc3339();
+        self.due_today_at(card_hashes, limits, day, chrono::Utc::now())
+            .await
+    }
 
+    async fn due_today_at(
+        &self,
+        card_hashes: &HashMap<String, Card>,
+        limits: SessionLimits,
+        day: DayBoundary,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Card>> {
+        let start_of_today = day.start_of_today(now);
+        let learn_ahead = (now + LEARN_AHEAD_THRESHOLD_MINS).to_rfc3339();
+        let next_day = day.start_of_next_day(now).to_rfc3339();
+        let now = now.to_rfc3339();
+
+        // review cards are due for the whole study day they fall on,
+        // cards in learning steps only once they are within the learn-ahead window
         // most overdue cards first
         // then cards due today
         // then new cards
+        // suspended cards and cards buried until later are skipped
+        // only one card of each multi-cloze or reversible card is taken; its siblings wait
+        // for a later session and don't use up the session's limits
         let mut rows = sqlx::query!(
             r#"
-        SELECT card_hash, review_count as "review_count!: i64"
+        SELECT
+            card_hash,
+            review_count as "review_count!: i64",
+            last_reviewed_at as "last_reviewed_at?: chrono::DateTime<chrono::Utc>"
         FROM cards
-        WHERE due_date <= ? OR due_date IS NULL
+        WHERE (due_date IS NULL OR due_date <= ? OR (phase = 'review' AND due_date < ?))
+            AND suspended = 0
+            AND (buried_until IS NULL OR buried_until <= ?)
         ORDER BY
             CASE WHEN due_date IS NULL THEN 1 ELSE 0 END,
             due_date ASC
         "#,
+            learn_ahead,
+            next_day,
             now
         )
         .fetch(&self.pool);
 
         let mut cards: Vec<Card> = Vec::new();
+        let mut taken = HashSet::new();
         let mut num_new_cards = 0;
+        let mut num_reviews = 0;
 
         while let Some(row) = rows.try_next().await? {
-            if !card_hashes.contains_key(&row.card_hash) {
+            let Some(card) = card_hashes.get(&row.card_hash) else {
+                continue;
+            };
+            if taken.contains(&(&card.file_path, card.file_card_range)) {
                 continue;
             }
 
             let is_new = row.review_count == 0;
+            // cards already reviewed today are learning steps, not part of the review budget
+            let is_review = !is_new
+                && row
+                    .last_reviewed_at
+                    .is_none_or(|reviewed_at| reviewed_at < start_of_today);
 
             if is_new
-                && let Some(limit) = new_card_limit
+                && let Some(limit) = limits.new_cards
                 && num_new_cards >= limit
             {
                 continue;
             }
+            if is_review
+                && let Some(limit) = limits.reviews
+                && num_reviews >= limit
+            {
+                continue;
+            }
 
-            if let Some(card) = card_hashes.get(&row.card_hash) {
-                cards.push(card.clone());
+            taken.insert((&card.file_path, card.file_card_range));
+            cards.push(card.clone());
 
-                if is_new {
-                    num_new_cards += 1;
-                }
+            if is_new {
+                num_new_cards += 1;
+            }
+            if is_review {
+                num_reviews += 1;
+            }
 
-                if let Some(limit) = card_limit
-                    && cards.len() >= limit
-                {
-                    break;
-                }
+            if let Some(limit) = limits.cards
+                && cards.len() >= limit
+            {
+                break;
             }
         }
 
         Ok(cards)
     }
+
+    /// Keep a card out of drills until `until`; it comes back on its own afterwards.
+    pub async fn bury_card(&self, card: &Card, until: DateTime<Utc>) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE cards
+            SET buried_until = ?
+            WHERE card_hash = ?
+            "#,
+            until,
+            card.card_hash
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Put cards back to the new state, as if they had never been reviewed. With
+    /// `clear_history`, their lapses, leech flag, and review log go too. Returns how
+    /// many cards were found.
+    pub async fn forget_cards(&self, cards: &[Card], clear_history: bool) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut changed = 0;
+        for card in cards {
+            changed += sqlx::query!(
+                r#"
+                UPDATE cards
+                SET
+                    last_reviewed_at = NULL,
+                    stability = NULL,
+                    difficulty = NULL,
+                    interval_raw = NULL,
+                    interval_days = 0,
+                    due_date = NULL,
+                    review_count = 0,
+                    phase = 'learning',
+                    step = 0,
+                    buried_until = NULL
+                WHERE card_hash = ?
+                "#,
+                card.card_hash
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if clear_history {
+                sqlx::query!(
+                    "UPDATE cards SET lapses = 0, leech = 0 WHERE card_hash = ?",
+                    card.card_hash
+                )
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query!("DELETE FROM revlog WHERE card_hash = ?", card.card_hash)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(changed)
+    }
+
+    /// Make cards due at `due`, keeping the rest of their state.
+    pub async fn set_due_date(&self, cards: &[Card], due: DateTime<Utc>) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut changed = 0;
+        for card in cards {
+            changed += sqlx::query!(
+                r#"
+                UPDATE cards
+                SET due_date = ?, buried_until = NULL
+                WHERE card_hash = ?
+                "#,
+                due,
+                card.card_hash
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+        tx.commit().await?;
+        Ok(changed)
+    }
+
+    /// Move cards to new review times. The review log is left alone.
+    pub async fn reschedule_cards(&self, changes: &[Reschedule]) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut changed = 0;
+        for change in changes {
+            changed += sqlx::query!(
+                r#"
+                UPDATE cards
+                SET last_reviewed_at = ?, due_date = ?
+                WHERE card_hash = ?
+                "#,
+                change.last_reviewed_at,
+                change.due_date,
+                change.card_hash
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+        tx.commit().await?;
+        Ok(changed)
+    }
+
+    /// Move each card's scheduling state and review log from its old hash to its new one,
+    /// given as `(from, to)` pairs. Pairs whose `to` is already known are skipped, so the
+    /// history there is never overwritten. Returns how many cards moved.
+    pub async fn move_card_history(&self, moves: &[(String, String)]) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut moved = 0;
+        for (from, to) in moves {
+            let affected = sqlx::query!(
+                r#"
+                UPDATE cards
+                SET card_hash = ?
+                WHERE card_hash = ?
+                    AND NOT EXISTS (SELECT 1 FROM cards WHERE card_hash = ?)
+                "#,
+                to,
+                from,
+                to
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if affected == 0 {
+                continue;
+            }
+            sqlx::query!(
+                "UPDATE revlog SET card_hash = ? WHERE card_hash = ?",
+                to,
+                from
+            )
+            .execute(&mut *tx)
+            .await?;
+            moved += affected;
+        }
+        tx.commit().await?;
+        Ok(moved)
+    }
+
+    /// Keep cards out of drills until they are unsuspended. Returns how many
+    /// cards were not already suspended.
+    pub async fn suspend_cards(&self, cards: &[Card]) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut changed = 0;
+        for card in cards {
+            changed += sqlx::query!(
+                r#"
+                UPDATE cards
+                SET suspended = 1
+                WHERE card_hash = ? AND suspended = 0
+                "#,
+                card.card_hash
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+        tx.commit().await?;
+        Ok(changed)
+    }
+
+    /// Return suspended or buried cards to drills. Returns how many cards changed.
+    pub async fn unsuspend_cards(&self, cards: &[Card]) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut changed = 0;
+        for card in cards {
+            changed += sqlx::query!(
+                r#"
+                UPDATE cards
+                SET suspended = 0, buried_until = NULL
+                WHERE card_hash = ? AND (suspended = 1 OR buried_until IS NOT NULL)
+                "#,
+                card.card_hash
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+        tx.commit().await?;
+        Ok(changed)
+    }
 }
 
 #[cfg(test)]
@@ -246,12 +757,15 @@ mod tests {
 
     use std::collections::HashMap;
     use std::path::PathBuf;
+    use std::time::Duration;
 
-    use crate::fsrs::{Performance, ReviewStatus};
+    use crate::crud::limits::SessionLimits;
+    use crate::fsrs::{Performance, Phase, ReviewStatus};
     use crate::parser::content_to_card;
     use crate::stats::CardLifeCycle;
+    use crate::utils::DayBoundary;
 
-    use super::DB;
+    use super::{DB, ReviewOptions};
 
     #[tokio::test]
     async fn follow_card_progress() {
@@ -268,18 +782,32 @@ mod tests {
 
         // should be in stats
         let card_hashes = HashMap::from([(card.card_hash.clone(), card.clone())]);
-        let stats = db.collection_stats(&card_hashes).await.unwrap();
+        let stats = db
+            .collection_stats(&card_hashes, DayBoundary::default())
+            .await
+            .unwrap();
         assert_eq!(stats.num_cards, 1);
         assert_eq!(stats.due_cards, 1);
         assert_eq!(stats.card_lifecycles.get(&CardLifeCycle::New).unwrap(), &1);
 
         // should be due today
-        let due_today_cards = db.due_today(&card_hashes, None, None).await.unwrap();
+        let due_today_cards = db
+            .due_today(
+                &card_hashes,
+                SessionLimits::default(),
+                DayBoundary::default(),
+            )
+            .await
+            .unwrap();
         assert_eq!(due_today_cards.len(), 1);
 
-        // check short-term scheduling
+        // check short-term scheduling, with exact FSRS intervals
+        let options = ReviewOptions {
+            fuzz: false,
+            ..ReviewOptions::default()
+        };
         for _ in 0..3 {
-            db.update_card_performance(&card, ReviewStatus::Pass, None, 0.9)
+            db.update_card_performance(&card, ReviewStatus::Good, None, &options, Duration::ZERO)
                 .await
                 .unwrap();
         }
@@ -294,9 +822,15 @@ mod tests {
 
         // wait the interval and then pass again
         let mut future_time = chrono::Utc::now() + chrono::Duration::days(1);
-        db.update_card_performance(&card, ReviewStatus::Pass, Some(future_time), 0.9)
-            .await
-            .unwrap();
+        db.update_card_performance(
+            &card,
+            ReviewStatus::Good,
+            Some(future_time),
+            &options,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
 
         match db.get_card_performance(&card).await.unwrap() {
             Performance::Reviewed(reviewed) => {
@@ -309,9 +843,15 @@ mod tests {
 
         // wait the interval and then pass again
         future_time += chrono::Duration::days(7);
-        db.update_card_performance(&card, ReviewStatus::Pass, Some(future_time), 0.9)
-            .await
-            .unwrap();
+        db.update_card_performance(
+            &card,
+            ReviewStatus::Good,
+            Some(future_time),
+            &options,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
 
         match db.get_card_performance(&card).await.unwrap() {
             Performance::Reviewed(reviewed) => {
@@ -324,31 +864,243 @@ mod tests {
 
         // now collapse it with a failure
         future_time += chrono::Duration::days(31);
-        db.update_card_performance(&card, ReviewStatus::Fail, Some(future_time), 0.9)
-            .await
-            .unwrap();
+        db.update_card_performance(
+            &card,
+            ReviewStatus::Again,
+            Some(future_time),
+            &options,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
 
         match db.get_card_performance(&card).await.unwrap() {
             Performance::Reviewed(reviewed) => {
                 assert_eq!(reviewed.review_count, 6);
-                assert_eq!(reviewed.interval_raw, 2.5044675925925928);
-                assert_eq!(reviewed.interval_days, 2);
+                assert_eq!(reviewed.interval_raw, 10.0 / (24.0 * 60.0));
+                assert_eq!(reviewed.interval_days, 0);
+                assert_eq!(reviewed.phase, Phase::Relearning { step: 1 });
             }
             _ => panic!(),
         }
 
-        // another failure
-        future_time += chrono::Duration::days(2);
-        db.update_card_performance(&card, ReviewStatus::Fail, Some(future_time), 0.9)
-            .await
-            .unwrap();
+        // pass the relearning step and go back to FSRS intervals
+        future_time += chrono::Duration::minutes(10);
+        db.update_card_performance(
+            &card,
+            ReviewStatus::Good,
+            Some(future_time),
+            &options,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
 
         match db.get_card_performance(&card).await.unwrap() {
             Performance::Reviewed(reviewed) => {
                 assert_eq!(reviewed.review_count, 7);
-                assert_eq!(reviewed.interval_raw, 0.5897800925925926);
+                assert_eq!(reviewed.phase, Phase::Review);
+                assert!(reviewed.interval_days >= 1);
             }
             _ => panic!(),
         }
     }
+
+    #[tokio::test]
+    async fn buried_cards_return_after_their_time() {
+        let db = DB::new_in_memory().await.unwrap();
+        let card = content_to_card(&PathBuf::from("test.md"), "Q: ping?\nA: pong", 1, 2).unwrap();
+        db.add_card(&card).await.unwrap();
+        let card_hashes = HashMap::from([(card.card_hash.clone(), card.clone())]);
+
+        let now = chrono::Utc::now();
+        db.bury_card(&card, now + chrono::Duration::hours(3))
+            .await
+            .unwrap();
+        assert!(
+            db.due_today(
+                &card_hashes,
+                SessionLimits::default(),
+                DayBoundary::default()
+            )
+            .await
+            .unwrap()
+            .is_empty()
+        );
+        let stats = db
+            .collection_stats(&card_hashes, DayBoundary::default())
+            .await
+            .unwrap();
+        assert_eq!(stats.buried_cards, 1);
+        assert_eq!(stats.due_cards, 0);
+
+        db.bury_card(&card, now - chrono::Duration::minutes(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            db.due_today(
+                &card_hashes,
+                SessionLimits::default(),
+                DayBoundary::default()
+            )
+            .await
+            .unwrap()
+            .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn undo_review_restores_previous_state() {
+        let db = DB::new_in_memory().await.unwrap();
+        let card = content_to_card(&PathBuf::from("test.md"), "Q: ping?\nA: pong", 1, 2).unwrap();
+        db.add_card(&card).await.unwrap();
+        let options = ReviewOptions::default();
+        let now = chrono::Utc::now();
+
+        db.update_card_performance(
+            &card,
+            ReviewStatus::Good,
+            Some(now),
+            &options,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
+        let after_first = db.get_card_performance(&card).await.unwrap();
+        let outcome = db
+            .update_card_performance(
+                &card,
+                ReviewStatus::Again,
+                Some(now + chrono::Duration::days(2)),
+                &options,
+                Duration::ZERO,
+            )
+            .await
+            .unwrap();
+        assert_eq!(outcome.lapses, 1);
+
+        db.undo_review(&card, &outcome.undo).await.unwrap();
+        assert_eq!(db.get_card_performance(&card).await.unwrap(), after_first);
+        assert_eq!(db.review_log().await.unwrap().len(), 1);
+
+        // the lapse was taken back too
+        let outcome = db
+            .update_card_performance(
+                &card,
+                ReviewStatus::Again,
+                Some(now + chrono::Duration::days(2)),
+                &options,
+                Duration::ZERO,
+            )
+            .await
+            .unwrap();
+        assert_eq!(outcome.lapses, 1);
+    }
+
+    #[tokio::test]
+    async fn review_cards_stay_due_until_the_day_rolls_over() {
+        let db = DB::new_in_memory().await.unwrap();
+        let card = content_to_card(&PathBuf::from("test.md"), "Q: ping?\nA: pong", 1, 2).unwrap();
+        db.add_card(&card).await.unwrap();
+        let card_hashes = HashMap::from([(card.card_hash.clone(), card.clone())]);
+
+        let day = DayBoundary::default();
+        // midday, well clear of the rollover
+        let now = day.start_of(chrono::NaiveDate::from_ymd_opt(2026, 3, 10).unwrap())
+            + chrono::Duration::hours(8);
+        let end_of_day = day.start_of_next_day(now) - chrono::Duration::minutes(1);
+        let set_due = async |phase: &str| {
+            sqlx::query(
+                "UPDATE cards SET review_count = 3, due_date = ?, phase = ? WHERE card_hash = ?",
+            )
+            .bind(end_of_day)
+            .bind(phase)
+            .bind(&card.card_hash)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        };
+
+        set_due("learning").await;
+        assert!(
+            db.due_today_at(&card_hashes, SessionLimits::default(), day, now)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        set_due("review").await;
+        assert_eq!(
+            db.due_today_at(&card_hashes, SessionLimits::default(), day, now)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn siblings_are_held_back_without_using_up_limits() {
+        let db = DB::new_in_memory().await.unwrap();
+        let path = PathBuf::from("test.md");
+        let mut cards =
+            crate::parser::content_to_cards(&path, "C: [a] and [b] and [c]", 0, 1).unwrap();
+        cards.push(content_to_card(&path, "Q: ping?\nA: pong", 3, 4).unwrap());
+        db.add_cards_batch(&cards).await.unwrap();
+        let card_hashes: HashMap<String, crate::card::Card> = cards
+            .iter()
+            .map(|card| (card.card_hash.clone(), card.clone()))
+            .collect();
+
+        let limits = SessionLimits {
+            new_cards: Some(2),
+            ..SessionLimits::default()
+        };
+        let due = db
+            .due_today(&card_hashes, limits, DayBoundary::default())
+            .await
+            .unwrap();
+        let mut starts: Vec<usize> = due.iter().map(|card| card.file_card_range.0).collect();
+        starts.sort();
+        assert_eq!(starts, vec![0, 3]);
+    }
+
+    #[tokio::test]
+    async fn review_intervals_are_fuzzed_per_card() {
+        let db = DB::new_in_memory().await.unwrap();
+        let path = PathBuf::from("test.md");
+        let cards: Vec<_> = (0..12)
+            .map(|i| content_to_card(&path, &format!("Q: {i}?\nA: {i}"), i * 3, i * 3 + 1).unwrap())
+            .collect();
+        db.add_cards_batch(&cards).await.unwrap();
+        let now = chrono::Utc::now();
+
+        let mut due_dates = std::collections::HashSet::new();
+        for card in &cards {
+            db.update_card_performance(
+                card,
+                ReviewStatus::Easy,
+                Some(now),
+                &ReviewOptions::default(),
+                Duration::ZERO,
+            )
+            .await
+            .unwrap();
+            let outcome = db
+                .update_card_performance(
+                    card,
+                    ReviewStatus::Good,
+                    Some(now + chrono::Duration::days(8)),
+                    &ReviewOptions::default(),
+                    Duration::ZERO,
+                )
+                .await
+                .unwrap();
+            assert_eq!(outcome.interval_raw.fract(), 0.0);
+            due_dates.insert(outcome.due_date);
+        }
+        // identical histories, but the cards no longer all come back on the same day
+        assert!(due_dates.len() > 1);
+    }
 }
@@ -0,0 +1,71 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use super::DB;
+
+pub const DEFAULT_LEECH_THRESHOLD: u32 = 8;
+
+/// What happens to a card once it becomes a leech.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeechAction {
+    /// Flag the card and keep drilling it.
+    #[default]
+    Tag,
+    /// Flag the card and suspend it until it is rewritten or unsuspended.
+    Suspend,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LeechPolicy {
+    /// Number of lapses after which a card is a leech.
+    pub threshold: u32,
+    pub action: LeechAction,
+}
+
+impl Default for LeechPolicy {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_LEECH_THRESHOLD,
+            action: LeechAction::default(),
+        }
+    }
+}
+
+pub struct LeechRow {
+    pub card_hash: String,
+    pub lapses: i64,
+    pub suspended: bool,
+}
+
+impl DB {
+    /// Every card flagged as a leech, most lapses first.
+    pub async fn leeches(&self) -> Result<Vec<LeechRow>> {
+        let rows = sqlx::query_as!(
+            LeechRow,
+            r#"
+            SELECT
+                card_hash,
+                lapses as "lapses!: i64",
+                suspended as "suspended!: bool"
+            FROM cards
+            WHERE leech = 1
+            ORDER BY lapses DESC, card_hash ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn leech_card_hashes(&self) -> Result<HashSet<String>> {
+        Ok(self
+            .leeches()
+            .await?
+            .into_iter()
+            .map(|row| row.card_hash)
+            .collect())
+    }
+}
@@ -0,0 +1,201 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use super::DB;
+
+/// Caps on how much is studied per day, across every session. `None` means no cap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DailyLimits {
+    pub new_cards: Option<usize>,
+    pub reviews: Option<usize>,
+}
+
+/// What has been studied so far today, according to the review log.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StudiedToday {
+    /// Cards seen for the first time today.
+    pub new_cards: usize,
+    /// Previously seen cards reviewed today, each counted once.
+    pub reviews: usize,
+}
+
+impl DailyLimits {
+    /// What is left of each cap after `studied`.
+    pub fn remaining(&self, studied: &StudiedToday) -> DailyLimits {
+        DailyLimits {
+            new_cards: self
+                .new_cards
+                .map(|limit| limit.saturating_sub(studied.new_cards)),
+            reviews: self
+                .reviews
+                .map(|limit| limit.saturating_sub(studied.reviews)),
+        }
+    }
+}
+
+/// Limits for building one session's queue.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SessionLimits {
+    /// Total cards in the session.
+    pub cards: Option<usize>,
+    pub new_cards: Option<usize>,
+    /// Cards last reviewed before today; cards already seen today are not counted again.
+    pub reviews: Option<usize>,
+}
+
+impl DB {
+    /// Count the new cards and reviews in the log since `start_of_today`.
+    pub async fn studied_since(&self, start_of_today: DateTime<Utc>) -> Result<StudiedToday> {
+        // the first review of a new card has no previous stability
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(DISTINCT CASE WHEN previous_stability IS NULL THEN card_hash END)
+                    as "new_cards!: i64",
+                COUNT(DISTINCT CASE
+                    WHEN previous_stability IS NOT NULL
+                        AND card_hash NOT IN (
+                            SELECT card_hash
+                            FROM revlog
+                            WHERE reviewed_at >= ?1
+                                AND previous_stability IS NULL
+                                AND kind = 'review'
+                        )
+                    THEN card_hash
+                END) as "reviews!: i64"
+            FROM revlog
+            WHERE reviewed_at >= ?1 AND kind = 'review'
+            "#,
+            start_of_today
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(StudiedToday {
+            new_cards: row.new_cards as usize,
+            reviews: row.reviews as usize,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::crud::cards::ReviewOptions;
+    use crate::fsrs::ReviewStatus;
+    use crate::parser::content_to_card;
+    use crate::utils::DayBoundary;
+
+    #[test]
+    fn remaining_never_goes_below_zero() {
+        let limits = DailyLimits {
+            new_cards: Some(5),
+            reviews: None,
+        };
+        let studied = StudiedToday {
+            new_cards: 7,
+            reviews: 40,
+        };
+        assert_eq!(
+            limits.remaining(&studied),
+            DailyLimits {
+                new_cards: Some(0),
+                reviews: None
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn counts_new_cards_and_reviews_once_each() {
+        let db = DB::new_in_memory().await.unwrap();
+        let path = PathBuf::from("test.md");
+        let old = content_to_card(&path, "Q: old?\nA: card", 1, 2).unwrap();
+        let new = content_to_card(&path, "Q: new?\nA: card", 4, 5).unwrap();
+        db.add_cards_batch(&[old.clone(), new.clone()])
+            .await
+            .unwrap();
+        let options = ReviewOptions::default();
+        let today = Utc::now();
+        let yesterday = today - chrono::Duration::days(1);
+
+        for (card, status, at) in [
+            (&old, ReviewStatus::Good, yesterday),
+            (&old, ReviewStatus::Again, today),
+            (&old, ReviewStatus::Good, today),
+            (&new, ReviewStatus::Again, today),
+            (&new, ReviewStatus::Good, today),
+        ] {
+            db.update_card_performance(card, status, Some(at), &options, Duration::ZERO)
+                .await
+                .unwrap();
+        }
+
+        let studied = db
+            .studied_since(today - chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            studied,
+            StudiedToday {
+                new_cards: 1,
+                reviews: 1
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn review_limit_skips_cards_already_seen_today() {
+        let db = DB::new_in_memory().await.unwrap();
+        let path = PathBuf::from("test.md");
+        let first = content_to_card(&path, "Q: first?\nA: card", 1, 2).unwrap();
+        let second = content_to_card(&path, "Q: second?\nA: card", 4, 5).unwrap();
+        db.add_cards_batch(&[first.clone(), second.clone()])
+            .await
+            .unwrap();
+        let card_hashes = HashMap::from([
+            (first.card_hash.clone(), first.clone()),
+            (second.card_hash.clone(), second.clone()),
+        ]);
+        let options = ReviewOptions::default();
+        let now = Utc::now();
+        let day = DayBoundary::default();
+        let limits = SessionLimits {
+            reviews: Some(1),
+            ..SessionLimits::default()
+        };
+
+        for card in [&first, &second] {
+            db.update_card_performance(
+                card,
+                ReviewStatus::Again,
+                Some(day.start_of_today(now) - chrono::Duration::hours(1)),
+                &options,
+                Duration::ZERO,
+            )
+            .await
+            .unwrap();
+        }
+        assert_eq!(
+            db.due_today(&card_hashes, limits, day).await.unwrap().len(),
+            1
+        );
+
+        db.update_card_performance(
+            &first,
+            ReviewStatus::Again,
+            Some(now - chrono::Duration::minutes(5)),
+            &options,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            db.due_today(&card_hashes, limits, day).await.unwrap().len(),
+            2
+        );
+    }
+}
@@ -1,5 +1,9 @@
 pub mod cards;
 pub mod db;
+pub mod leeches;
+pub mod limits;
+pub mod parameters;
+pub mod revlog;
 pub mod stats;
 pub mod version;
 
@@ -0,0 +1,112 @@
+use anyhow::{Context, Result};
+use fsrs::DEFAULT_PARAMETERS;
+
+use super::DB;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoredParameters {
+    pub parameters: Vec<f32>,
+    pub review_count: i64,
+    pub optimized_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl DB {
+    /// Personalized parameters saved by `repeater optimize`, if any.
+    pub async fn stored_fsrs_parameters(&self) -> Result<Option<StoredParameters>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                parameters,
+                review_count as "review_count!: i64",
+                optimized_at as "optimized_at!: chrono::DateTime<chrono::Utc>"
+            FROM fsrs_parameters
+            WHERE id = 1
+            "#
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let parameters: Vec<f32> = serde_json::from_str(&row.parameters)
+            .context("failed to parse stored FSRS parameters")?;
+        Ok(Some(StoredParameters {
+            parameters,
+            review_count: row.review_count,
+            optimized_at: row.optimized_at,
+        }))
+    }
+
+    /// The parameters scheduling should use: personalized if available, otherwise the defaults.
+    pub async fn fsrs_parameters(&self) -> Result<Vec<f32>> {
+        Ok(self
+            .stored_fsrs_parameters()
+            .await?
+            .map(|stored| stored.parameters)
+            .unwrap_or_else(|| DEFAULT_PARAMETERS.to_vec()))
+    }
+
+    pub async fn save_fsrs_parameters(&self, parameters: &[f32], review_count: i64) -> Result<()> {
+        let encoded = serde_json::to_string(parameters)?;
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query!(
+            r#"
+            INSERT INTO fsrs_parameters (id, parameters, review_count, optimized_at)
+            VALUES (1, ?, ?, ?)
+            ON CONFLICT (id)
+            DO UPDATE SET
+                parameters = EXCLUDED.parameters,
+                review_count = EXCLUDED.review_count,
+                optimized_at = EXCLUDED.optimized_at
+            "#,
+            encoded,
+            review_count,
+            now
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn clear_fsrs_parameters(&self) -> Result<bool> {
+        let result = sqlx::query!("DELETE FROM fsrs_parameters")
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fsrs::DEFAULT_PARAMETERS;
+
+    use super::DB;
+
+    #[tokio::test]
+    async fn parameters_round_trip() {
+        let db = DB::new_in_memory().await.unwrap();
+        assert!(db.stored_fsrs_parameters().await.unwrap().is_none());
+        assert_eq!(
+            db.fsrs_parameters().await.unwrap(),
+            DEFAULT_PARAMETERS.to_vec()
+        );
+
+        let mut personalized = DEFAULT_PARAMETERS.to_vec();
+        personalized[0] = 0.5;
+        db.save_fsrs_parameters(&personalized, 120).await.unwrap();
+
+        let stored = db.stored_fsrs_parameters().await.unwrap().unwrap();
+        assert_eq!(stored.parameters, personalized);
+        assert_eq!(stored.review_count, 120);
+        assert_eq!(db.fsrs_parameters().await.unwrap(), personalized);
+
+        assert!(db.clear_fsrs_parameters().await.unwrap());
+        assert!(!db.clear_fsrs_parameters().await.unwrap());
+        assert_eq!(
+            db.fsrs_parameters().await.unwrap(),
+            DEFAULT_PARAMETERS.to_vec()
+        );
+    }
+}
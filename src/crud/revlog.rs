@@ -0,0 +1,403 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use sqlx::{Sqlite, Transaction};
+
+use crate::fsrs::ReviewStatus;
+
+use super::DB;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReviewLogEntry {
+    pub id: i64,
+    pub card_hash: String,
+    pub reviewed_at: DateTime<Utc>,
+    pub grade: ReviewStatus,
+    pub elapsed_days: f64,
+    pub previous_stability: Option<f64>,
+    pub previous_difficulty: Option<f64>,
+    pub stability: f64,
+    pub difficulty: f64,
+    pub interval_raw: f64,
+    pub time_spent: std::time::Duration,
+}
+
+pub struct ReviewLogRow {
+    pub id: i64,
+    pub card_hash: String,
+    pub reviewed_at: DateTime<Utc>,
+    pub grade: i64,
+    pub elapsed_days: f64,
+    pub previous_stability: Option<f64>,
+    pub previous_difficulty: Option<f64>,
+    pub stability: f64,
+    pub difficulty: f64,
+    pub interval_raw: f64,
+    pub time_spent_ms: i64,
+}
+
+impl TryFrom<ReviewLogRow> for ReviewLogEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(row: ReviewLogRow) -> Result<Self> {
+        let grade = u32::try_from(row.grade)
+            .ok()
+            .and_then(ReviewStatus::from_rating)
+            .ok_or_else(|| anyhow!("invalid grade {} in review log {}", row.grade, row.id))?;
+        Ok(Self {
+            id: row.id,
+            card_hash: row.card_hash,
+            reviewed_at: row.reviewed_at,
+            grade,
+            elapsed_days: row.elapsed_days,
+            previous_stability: row.previous_stability,
+            previous_difficulty: row.previous_difficulty,
+            stability: row.stability,
+            difficulty: row.difficulty,
+            interval_raw: row.interval_raw,
+            time_spent: std::time::Duration::from_millis(row.time_spent_ms.max(0) as u64),
+        })
+    }
+}
+
+/// Whether a logged review moved the card's schedule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReviewKind {
+    Review,
+    /// Graded in a cram session; the card's scheduling was left alone.
+    Cram,
+}
+
+impl ReviewKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewKind::Review => "review",
+            ReviewKind::Cram => "cram",
+        }
+    }
+}
+
+/// A review about to be written to the log.
+pub struct NewReviewLog<'a> {
+    pub kind: ReviewKind,
+    pub card_hash: &'a str,
+    pub reviewed_at: DateTime<Utc>,
+    pub grade: ReviewStatus,
+    pub elapsed_days: f64,
+    pub previous_stability: Option<f64>,
+    pub previous_difficulty: Option<f64>,
+    pub stability: f64,
+    pub difficulty: f64,
+    pub interval_raw: f64,
+    pub time_spent: std::time::Duration,
+}
+
+impl DB {
+    pub(super) async fn insert_review_log(
+        tx: &mut Transaction<'_, Sqlite>,
+        review: NewReviewLog<'_>,
+    ) -> Result<i64> {
+        let grade = review.grade.rating() as i64;
+        let time_spent_ms = review.time_spent.as_millis() as i64;
+        let kind = review.kind.as_str();
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO revlog (
+                card_hash,
+                reviewed_at,
+                grade,
+                elapsed_days,
+                previous_stability,
+                previous_difficulty,
+                stability,
+                difficulty,
+                interval_raw,
+                time_spent_ms,
+                kind
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            review.card_hash,
+            review.reviewed_at,
+            grade,
+            review.elapsed_days,
+            review.previous_stability,
+            review.previous_difficulty,
+            review.stability,
+            review.difficulty,
+            review.interval_raw,
+            time_spent_ms,
+            kind,
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    pub(super) async fn last_review_grade(
+        tx: &mut Transaction<'_, Sqlite>,
+        card_hash: &str,
+    ) -> Result<Option<ReviewStatus>> {
+        let grade = sqlx::query_scalar!(
+            r#"
+            SELECT grade
+            FROM revlog
+            WHERE card_hash = ? AND kind = 'review'
+            ORDER BY reviewed_at DESC, id DESC
+            LIMIT 1
+            "#,
+            card_hash
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        Ok(grade
+            .and_then(|grade| u32::try_from(grade).ok())
+            .and_then(ReviewStatus::from_rating))
+    }
+
+    pub async fn review_log_for_card(&self, card_hash: &str) -> Result<Vec<ReviewLogEntry>> {
+        let rows = sqlx::query_as!(
+            ReviewLogRow,
+            r#"
+            SELECT
+                id as "id!: i64",
+                card_hash,
+                reviewed_at as "reviewed_at!: DateTime<Utc>",
+                grade,
+                elapsed_days,
+                previous_stability,
+                previous_difficulty,
+                stability,
+                difficulty,
+                interval_raw,
+                time_spent_ms
+            FROM revlog
+            WHERE card_hash = ? AND kind = 'review'
+            ORDER BY reviewed_at ASC, id ASC
+            "#,
+            card_hash
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(ReviewLogEntry::try_from).collect()
+    }
+
+    /// Log a cram-session grade without touching the card's scheduling. Returns the log id.
+    pub async fn log_cram_review(
+        &self,
+        card_hash: &str,
+        grade: ReviewStatus,
+        reviewed_at: DateTime<Utc>,
+        time_spent: std::time::Duration,
+    ) -> Result<i64> {
+        let mut tx = self.pool.begin().await?;
+        let current = sqlx::query!(
+            r#"
+            SELECT
+                last_reviewed_at as "last_reviewed_at?: DateTime<Utc>",
+                stability,
+                difficulty,
+                interval_raw
+            FROM cards
+            WHERE card_hash = ?
+            "#,
+            card_hash
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let elapsed_days = current.last_reviewed_at.map_or(0.0, |last| {
+            reviewed_at.signed_duration_since(last).num_seconds().max(0) as f64 / 86_400.0
+        });
+        let id = Self::insert_review_log(
+            &mut tx,
+            NewReviewLog {
+                kind: ReviewKind::Cram,
+                card_hash,
+                reviewed_at,
+                grade,
+                elapsed_days,
+                previous_stability: current.stability,
+                previous_difficulty: current.difficulty,
+                stability: current.stability.unwrap_or_default(),
+                difficulty: current.difficulty.unwrap_or_default(),
+                interval_raw: current.interval_raw.unwrap_or_default(),
+                time_spent,
+            },
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    /// Drop a cram-session grade logged by [`DB::log_cram_review`].
+    pub async fn delete_cram_review(&self, id: i64) -> Result<()> {
+        sqlx::query!("DELETE FROM revlog WHERE id = ? AND kind = 'cram'", id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Every logged review, grouped by card and in the order they happened.
+    pub async fn review_log(&self) -> Result<Vec<ReviewLogEntry>> {
+        let rows = sqlx::query_as!(
+            ReviewLogRow,
+            r#"
+            SELECT
+                id as "id!: i64",
+                card_hash,
+                reviewed_at as "reviewed_at!: DateTime<Utc>",
+                grade,
+                elapsed_days,
+                previous_stability,
+                previous_difficulty,
+                stability,
+                difficulty,
+                interval_raw,
+                time_spent_ms
+            FROM revlog
+            WHERE kind = 'review'
+            ORDER BY card_hash ASC, reviewed_at ASC, id ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(ReviewLogEntry::try_from).collect()
+    }
+
+    pub async fn review_log_since(&self, since: DateTime<Utc>) -> Result<Vec<ReviewLogEntry>> {
+        let rows = sqlx::query_as!(
+            ReviewLogRow,
+            r#"
+            SELECT
+                id as "id!: i64",
+                card_hash,
+                reviewed_at as "reviewed_at!: DateTime<Utc>",
+                grade,
+                elapsed_days,
+                previous_stability,
+                previous_difficulty,
+                stability,
+                difficulty,
+                interval_raw,
+                time_spent_ms
+            FROM revlog
+            WHERE reviewed_at >= ? AND kind = 'review'
+            ORDER BY reviewed_at ASC, id ASC
+            "#,
+            since
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(ReviewLogEntry::try_from).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use crate::crud::cards::ReviewOptions;
+    use crate::fsrs::ReviewStatus;
+    use crate::parser::content_to_card;
+
+    use super::DB;
+
+    #[tokio::test]
+    async fn logs_every_review() {
+        let db = DB::new_in_memory().await.unwrap();
+        let card = content_to_card(&PathBuf::from("test.md"), "Q: ping?\nA: pong", 1, 2).unwrap();
+        db.add_card(&card).await.unwrap();
+
+        let start = chrono::Utc::now();
+        db.update_card_performance(
+            &card,
+            ReviewStatus::Good,
+            Some(start),
+            &ReviewOptions::default(),
+            Duration::from_millis(1500),
+        )
+        .await
+        .unwrap();
+        db.update_card_performance(
+            &card,
+            ReviewStatus::Again,
+            Some(start + chrono::Duration::days(2)),
+            &ReviewOptions::default(),
+            Duration::from_secs(4),
+        )
+        .await
+        .unwrap();
+
+        let log = db.review_log_for_card(&card.card_hash).await.unwrap();
+        assert_eq!(log.len(), 2);
+
+        let first = &log[0];
+        assert_eq!(first.grade, ReviewStatus::Good);
+        assert_eq!(first.reviewed_at, start);
+        assert_eq!(first.elapsed_days, 0.0);
+        assert!(first.previous_stability.is_none());
+        assert!(first.previous_difficulty.is_none());
+        assert_eq!(first.time_spent, Duration::from_millis(1500));
+
+        let second = &log[1];
+        assert_eq!(second.grade, ReviewStatus::Again);
+        assert!((second.elapsed_days - 2.0).abs() < 1e-6);
+        assert_eq!(second.previous_stability, Some(first.stability));
+        assert_eq!(second.previous_difficulty, Some(first.difficulty));
+        assert_eq!(second.time_spent, Duration::from_secs(4));
+
+        assert_eq!(db.review_log().await.unwrap(), log);
+        let recent = db
+            .review_log_since(start + chrono::Duration::days(1))
+            .await
+            .unwrap();
+        assert_eq!(recent, vec![second.clone()]);
+    }
+
+    #[tokio::test]
+    async fn cram_reviews_stay_out_of_the_history() {
+        let db = DB::new_in_memory().await.unwrap();
+        let card = content_to_card(&PathBuf::from("test.md"), "Q: ping?\nA: pong", 1, 2).unwrap();
+        db.add_card(&card).await.unwrap();
+
+        let id = db
+            .log_cram_review(
+                &card.card_hash,
+                ReviewStatus::Good,
+                chrono::Utc::now(),
+                Duration::from_secs(2),
+            )
+            .await
+            .unwrap();
+        let crammed: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM revlog WHERE kind = 'cram'")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(crammed, 1);
+        assert!(db.review_log().await.unwrap().is_empty());
+        assert!(
+            db.review_log_for_card(&card.card_hash)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let studied = db
+            .studied_since(chrono::Utc::now() - chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(studied.new_cards, 0);
+
+        db.delete_cram_review(id).await.unwrap();
+        let crammed: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM revlog")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(crammed, 0);
+    }
+}
@@ -4,9 +4,12 @@ use std::collections::HashMap;
 
 use crate::card::Card;
 
+use fsrs::{MemoryState, current_retrievability};
 use futures::TryStreamExt;
 
+use crate::fsrs::decay;
 use crate::stats::CardStats;
+use crate::utils::DayBoundary;
 use anyhow::Result;
 
 pub struct CardStatsRow {
@@ -17,14 +20,50 @@ pub struct CardStatsRow {
     pub difficulty: Option<f64>,
     pub stability: Option<f64>,
     pub last_reviewed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub suspended: bool,
+    pub buried_until: Option<chrono::DateTime<chrono::Utc>>,
+    pub phase: String,
+}
+
+impl CardStatsRow {
+    /// Chance of recalling the card at `now`, or `None` if it was never reviewed.
+    pub fn retrievability(&self, now: chrono::DateTime<chrono::Utc>, decay: f32) -> Option<f32> {
+        let (Some(stability), Some(last_reviewed_at)) = (self.stability, self.last_reviewed_at)
+        else {
+            return None;
+        };
+        let elapsed_days = now
+            .signed_duration_since(last_reviewed_at)
+            .num_seconds()
+            .max(0) as f32
+            / 86_400.0;
+        Some(current_retrievability(
+            MemoryState {
+                stability: stability as f32,
+                difficulty: self.difficulty.unwrap_or_default() as f32,
+            },
+            elapsed_days,
+            decay,
+        ))
+    }
 }
 
 impl DB {
-    pub async fn collection_stats(&self, card_hashes: &HashMap<String, Card>) -> Result<CardStats> {
+    pub async fn collection_stats(
+        &self,
+        card_hashes: &HashMap<String, Card>,
+        day: DayBoundary,
+    ) -> Result<CardStats> {
+        let studied_today = self
+            .studied_since(day.start_of_today(chrono::Utc::now()))
+            .await?;
         let mut stats = CardStats {
+            day,
+            studied_today,
             num_cards: card_hashes.len() as i64,
             ..Default::default()
         };
+        let decay = decay(&self.fsrs_parameters().await?);
 
         let mut rows = sqlx::query_as!(
             CardStatsRow,
@@ -36,7 +75,10 @@ impl DB {
                 interval_raw as "interval_raw?: f64",
                 difficulty as "difficulty?: f64",
                 stability as "stability?: f64",
-                last_reviewed_at as "last_reviewed_at?: chrono::DateTime<chrono::Utc>"
+                last_reviewed_at as "last_reviewed_at?: chrono::DateTime<chrono::Utc>",
+                suspended as "suspended!: bool",
+                buried_until as "buried_until?: chrono::DateTime<chrono::Utc>",
+                phase
             FROM cards
             "#,
         )
@@ -48,9 +90,40 @@ impl DB {
                 Some(card) => card,
                 None => continue,
             };
-            stats.update(card, &row);
+            stats.update(card, &row, decay);
         }
 
         Ok(stats)
     }
+
+    /// Scheduling state for the cards in `card_hashes`, skipping stale rows.
+    pub async fn card_stats_rows(
+        &self,
+        card_hashes: &HashMap<String, Card>,
+    ) -> Result<Vec<CardStatsRow>> {
+        let rows = sqlx::query_as!(
+            CardStatsRow,
+            r#"
+            SELECT
+                card_hash,
+                review_count as "review_count!: i64",
+                due_date as "due_date?: chrono::DateTime<chrono::Utc>",
+                interval_raw as "interval_raw?: f64",
+                difficulty as "difficulty?: f64",
+                stability as "stability?: f64",
+                last_reviewed_at as "last_reviewed_at?: chrono::DateTime<chrono::Utc>",
+                suspended as "suspended!: bool",
+                buried_until as "buried_until?: chrono::DateTime<chrono::Utc>",
+                phase
+            FROM cards
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter(|row| card_hashes.contains_key(&row.card_hash))
+            .collect())
+    }
 }
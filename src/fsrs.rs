@@ -1,39 +1,211 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use chrono::{DateTime, Duration, Utc};
-use fsrs::{DEFAULT_PARAMETERS, FSRS, MemoryState};
+use fsrs::{FSRS, FSRS6_DEFAULT_DECAY, MemoryState};
 
 const SECONDS_PER_DAY: f64 = 86_400.0;
 
 pub const LEARN_AHEAD_THRESHOLD_MINS: Duration = Duration::minutes(20);
 
-fn early_interval_cap(review_count: usize, review_status: ReviewStatus) -> Option<Duration> {
-    match review_count {
-        0 => Some(Duration::minutes(1)),
-        1 => match review_status {
-            ReviewStatus::Pass => Some(Duration::minutes(10)),
-            ReviewStatus::Fail => Some(Duration::minutes(1)),
-        },
-        2 => match review_status {
-            ReviewStatus::Pass => Some(Duration::days(1)),
-            ReviewStatus::Fail => Some(Duration::minutes(10)),
-        },
-        _ => None,
+/// Where a reviewed card is in its short-term steps.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// A new card working through the learning steps. `step` counts the steps already waited.
+    Learning { step: usize },
+    /// Graduated; FSRS picks every interval.
+    Review,
+    /// A lapsed card working back through the relearning steps.
+    Relearning { step: usize },
+}
+
+impl Phase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Learning { .. } => "learning",
+            Phase::Review => "review",
+            Phase::Relearning { .. } => "relearning",
+        }
+    }
+
+    pub fn step(&self) -> usize {
+        match self {
+            Phase::Learning { step } | Phase::Relearning { step } => *step,
+            Phase::Review => 0,
+        }
+    }
+
+    pub fn from_parts(phase: &str, step: usize) -> Option<Self> {
+        match phase {
+            "learning" => Some(Phase::Learning { step }),
+            "review" => Some(Phase::Review),
+            "relearning" => Some(Phase::Relearning { step }),
+            _ => None,
+        }
+    }
+}
+
+/// Same-day delays a card waits before FSRS takes over, for new cards and
+/// for cards that were forgotten after graduating.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LearningSteps {
+    pub learning: Vec<Duration>,
+    pub relearning: Vec<Duration>,
+}
+
+impl Default for LearningSteps {
+    fn default() -> Self {
+        Self {
+            learning: vec![
+                Duration::minutes(1),
+                Duration::minutes(10),
+                Duration::days(1),
+            ],
+            relearning: vec![Duration::minutes(10)],
+        }
+    }
+}
+
+impl LearningSteps {
+    /// The phase a card moves to after this answer, and the step delay to wait
+    /// instead of the FSRS interval (`None` once the card is scheduled by FSRS).
+    fn next(&self, phase: Phase, review_status: ReviewStatus) -> (Phase, Option<Duration>) {
+        match phase {
+            Phase::Learning { step } => match walk_steps(&self.learning, step, review_status) {
+                Some((step, delay)) => (Phase::Learning { step }, Some(delay)),
+                None => (Phase::Review, None),
+            },
+            Phase::Relearning { step } => match walk_steps(&self.relearning, step, review_status) {
+                Some((step, delay)) => (Phase::Relearning { step }, Some(delay)),
+                None => (Phase::Review, None),
+            },
+            Phase::Review => match (review_status, self.relearning.first()) {
+                (ReviewStatus::Again, Some(delay)) => (Phase::Relearning { step: 1 }, Some(*delay)),
+                _ => (Phase::Review, None),
+            },
+        }
+    }
+}
+
+/// Bounds on the intervals FSRS picks, in days.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IntervalLimits {
+    pub maximum: f64,
+    /// Shortest interval for a forgotten card once it is back under FSRS; `0` leaves it to FSRS.
+    pub minimum_after_lapse: f64,
+}
+
+impl Default for IntervalLimits {
+    fn default() -> Self {
+        Self {
+            maximum: 36_500.0,
+            minimum_after_lapse: 0.0,
+        }
+    }
+}
+
+impl IntervalLimits {
+    pub fn new(maximum: f64, minimum_after_lapse: f64) -> Result<Self> {
+        if maximum < 1.0 {
+            bail!("The maximum interval must be at least 1 day");
+        }
+        if minimum_after_lapse > maximum {
+            bail!("The minimum interval after a lapse can't be longer than the maximum interval");
+        }
+        Ok(Self {
+            maximum,
+            minimum_after_lapse,
+        })
+    }
+
+    /// Keep `interval_days` within the limits; `lapsed` also applies the minimum after a lapse.
+    pub fn clamp(&self, interval_days: f64, lapsed: bool) -> f64 {
+        let interval_days = if lapsed {
+            interval_days.max(self.minimum_after_lapse)
+        } else {
+            interval_days
+        };
+        interval_days.min(self.maximum)
     }
 }
 
-#[derive(Copy, Clone, PartialEq)]
+pub fn validate_retention(retention: f32) -> Result<()> {
+    if retention > 1.0 {
+        bail!("Retention must be less than or equal to 1.0")
+    }
+    if retention < 0.65 {
+        bail!("Retention must be greater than 0.65")
+    }
+    Ok(())
+}
+
+/// Again restarts the steps, Hard waits between the last and the next step,
+/// Good waits the next step, and Easy or Good after the last step graduates.
+fn walk_steps(
+    steps: &[Duration],
+    step: usize,
+    review_status: ReviewStatus,
+) -> Option<(usize, Duration)> {
+    let first = *steps.first()?;
+    match review_status {
+        ReviewStatus::Again => Some((1, first)),
+        ReviewStatus::Hard if step == 0 => Some((1, first)),
+        ReviewStatus::Hard => {
+            let previous = steps[(step - 1).min(steps.len() - 1)];
+            let next = steps.get(step).copied().unwrap_or(previous);
+            Some((step, (previous + next) / 2))
+        }
+        ReviewStatus::Good => steps.get(step).map(|delay| (step + 1, *delay)),
+        ReviewStatus::Easy => None,
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ReviewStatus {
-    Pass,
-    Fail,
+    Again,
+    Hard,
+    Good,
+    Easy,
 }
 
 impl ReviewStatus {
+    pub const ALL: [ReviewStatus; 4] = [
+        ReviewStatus::Again,
+        ReviewStatus::Hard,
+        ReviewStatus::Good,
+        ReviewStatus::Easy,
+    ];
+
     pub fn label(&self) -> &'static str {
         match self {
-            ReviewStatus::Pass => "Pass",
-            ReviewStatus::Fail => "Fail",
+            ReviewStatus::Again => "Again",
+            ReviewStatus::Hard => "Hard",
+            ReviewStatus::Good => "Good",
+            ReviewStatus::Easy => "Easy",
+        }
+    }
+
+    /// The FSRS rating (1-4), which is also the drill key bound to this grade.
+    pub fn rating(&self) -> u32 {
+        match self {
+            ReviewStatus::Again => 1,
+            ReviewStatus::Hard => 2,
+            ReviewStatus::Good => 3,
+            ReviewStatus::Easy => 4,
+        }
+    }
+
+    pub fn from_rating(rating: u32) -> Option<Self> {
+        match rating {
+            1 => Some(ReviewStatus::Again),
+            2 => Some(ReviewStatus::Hard),
+            3 => Some(ReviewStatus::Good),
+            4 => Some(ReviewStatus::Easy),
+            _ => None,
         }
     }
+
+    pub fn is_fail(&self) -> bool {
+        *self == ReviewStatus::Again
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -45,6 +217,7 @@ pub struct ReviewedPerformance {
     pub interval_days: usize,
     pub due_date: DateTime<Utc>,
     pub review_count: usize,
+    pub phase: Phase,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
@@ -54,8 +227,13 @@ pub enum Performance {
     Reviewed(ReviewedPerformance),
 }
 
-fn fsrs_model() -> Result<FSRS> {
-    FSRS::new(Some(&DEFAULT_PARAMETERS)).context("failed to initialize FSRS model")
+fn fsrs_model(parameters: &[f32]) -> Result<FSRS> {
+    FSRS::new(Some(parameters)).context("failed to initialize FSRS model")
+}
+
+/// The forgetting curve decay encoded in a set of FSRS-6 parameters.
+pub fn decay(parameters: &[f32]) -> f32 {
+    parameters.get(20).copied().unwrap_or(FSRS6_DEFAULT_DECAY)
 }
 
 fn next_state_for_review(
@@ -63,24 +241,36 @@ fn next_state_for_review(
     review_status: ReviewStatus,
 ) -> fsrs::ItemState {
     match review_status {
-        ReviewStatus::Pass => next_states.good,
-        ReviewStatus::Fail => next_states.again,
+        ReviewStatus::Again => next_states.again,
+        ReviewStatus::Hard => next_states.hard,
+        ReviewStatus::Good => next_states.good,
+        ReviewStatus::Easy => next_states.easy,
     }
 }
 
+/// Whether grading a card in `phase` ends in a lapse: a card forgotten in review, or one
+/// leaving its relearning steps.
+pub fn is_lapse(phase: Phase, review_status: ReviewStatus) -> bool {
+    matches!(phase, Phase::Relearning { .. }) || (phase == Phase::Review && review_status.is_fail())
+}
+
 pub fn update_performance(
     perf: Performance,
     review_status: ReviewStatus,
     reviewed_at: DateTime<Utc>,
     retention: f32,
+    parameters: &[f32],
+    steps: &LearningSteps,
+    limits: &IntervalLimits,
 ) -> Result<ReviewedPerformance> {
-    let (memory_state, last_reviewed_at, review_count) = match perf {
-        Performance::New => (None, None, 0),
+    let (memory_state, last_reviewed_at, review_count, phase) = match perf {
+        Performance::New => (None, None, 0, Phase::Learning { step: 0 }),
         Performance::Reviewed(ReviewedPerformance {
             last_reviewed_at,
             stability,
             difficulty,
             review_count,
+            phase,
             ..
         }) => (
             Some(MemoryState {
@@ -89,33 +279,35 @@ pub fn update_performance(
             }),
             Some(last_reviewed_at),
             review_count,
+            phase,
         ),
     };
 
     let elapsed_days = last_reviewed_at
         .map(|last| {
-            if review_count < 3 {
-                return 0;
-            }
             let secs = reviewed_at.signed_duration_since(last).num_seconds().max(0) as f64;
             let days = secs / SECONDS_PER_DAY;
-            // Ensure sub-day intervals count as at least 1 day for FSRS,
-            // allowing stability to update
-            days.max(1.0).round() as u32
+            match phase {
+                // Ensure sub-day intervals count as at least 1 day for FSRS,
+                // allowing stability to update
+                Phase::Review => days.max(1.0).round() as u32,
+                // Same-day steps are short-term reviews for FSRS
+                Phase::Learning { .. } | Phase::Relearning { .. } => days.floor() as u32,
+            }
         })
         .unwrap_or(0);
 
-    let fsrs = fsrs_model()?;
+    let fsrs = fsrs_model(parameters)?;
     let next_states = fsrs.next_states(memory_state, retention, elapsed_days)?;
     let next_state = next_state_for_review(next_states, review_status);
 
-    let interval_raw = next_state.interval as f64;
+    let (next_phase, step_delay) = steps.next(phase, review_status);
+
+    let lapsed = is_lapse(phase, review_status);
+    let interval_raw = limits.clamp(next_state.interval as f64, lapsed);
     let fsrs_seconds = (interval_raw * SECONDS_PER_DAY).round().max(1.0) as i64;
     let fsrs_duration = Duration::seconds(fsrs_seconds);
-
-    let interval_duration = early_interval_cap(review_count, review_status)
-        .map(|cap| fsrs_duration.min(cap))
-        .unwrap_or(fsrs_duration);
+    let interval_duration = step_delay.unwrap_or(fsrs_duration);
 
     let interval_effective_days = interval_duration.num_seconds() as f64 / SECONDS_PER_DAY;
     let interval_days = interval_duration.num_days().max(0) as usize;
@@ -129,13 +321,45 @@ pub fn update_performance(
         interval_days,
         due_date,
         review_count: review_count + 1,
+        phase: next_phase,
     })
 }
 
+/// Interval (in days) the card would be scheduled for under each grade, in
+/// `ReviewStatus::ALL` order.
+pub fn preview_intervals(
+    perf: Performance,
+    reviewed_at: DateTime<Utc>,
+    retention: f32,
+    parameters: &[f32],
+    steps: &LearningSteps,
+    limits: &IntervalLimits,
+) -> Result<Vec<(ReviewStatus, f64)>> {
+    ReviewStatus::ALL
+        .into_iter()
+        .map(|status| {
+            update_performance(
+                perf,
+                status,
+                reviewed_at,
+                retention,
+                parameters,
+                steps,
+                limits,
+            )
+            .map(|reviewed| (status, reviewed.interval_raw))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Performance, ReviewStatus, ReviewedPerformance, update_performance};
+    use super::{
+        IntervalLimits, LearningSteps, Performance, Phase, ReviewStatus, ReviewedPerformance,
+        preview_intervals, update_performance,
+    };
     use chrono::Duration;
+    use fsrs::DEFAULT_PARAMETERS;
     fn approx_eq(a: f64, b: f64) -> bool {
         (a - b).abs() < 1e-2
     }
@@ -144,7 +368,15 @@ mod tests {
     fn test_update_new_card() {
         let reviewed_at = chrono::Utc::now();
 
-        let result = update_performance(Performance::New, ReviewStatus::Pass, reviewed_at, 0.9);
+        let result = update_performance(
+            Performance::New,
+            ReviewStatus::Good,
+            reviewed_at,
+            0.9,
+            &DEFAULT_PARAMETERS,
+            &LearningSteps::default(),
+            &IntervalLimits::default(),
+        );
         dbg!(result.as_ref().unwrap());
         let ReviewedPerformance {
             last_reviewed_at,
@@ -154,6 +386,7 @@ mod tests {
             interval_days,
             due_date: _,
             review_count,
+            phase,
         } = result.unwrap();
         assert_eq!(last_reviewed_at, reviewed_at);
         assert!(approx_eq(stability, 2.30649995803833));
@@ -161,6 +394,7 @@ mod tests {
         assert!(approx_eq(interval_raw, 0.0006944444444444445));
         assert_eq!(interval_days, 0);
         assert_eq!(review_count, 1);
+        assert_eq!(phase, Phase::Learning { step: 1 });
     }
 
     #[test]
@@ -176,12 +410,16 @@ mod tests {
             interval_days: 3,
             due_date: now,
             review_count: 1,
+            phase: Phase::Learning { step: 1 },
         };
         let result = update_performance(
             Performance::Reviewed(initial_perf),
-            ReviewStatus::Pass,
+            ReviewStatus::Good,
             now,
             0.9,
+            &DEFAULT_PARAMETERS,
+            &LearningSteps::default(),
+            &IntervalLimits::default(),
         )
         .unwrap();
         assert_eq!(result.last_reviewed_at, now);
@@ -200,20 +438,30 @@ mod tests {
             interval_days: 4,
             due_date: now + Duration::days(4),
             review_count: 3,
+            phase: Phase::Review,
         };
         let result = update_performance(
             Performance::Reviewed(initial_perf),
-            ReviewStatus::Fail,
+            ReviewStatus::Again,
             now,
             0.9,
+            &DEFAULT_PARAMETERS,
+            &LearningSteps::default(),
+            &IntervalLimits::default(),
         )
         .unwrap();
-        assert_eq!(result.interval_raw, 0.7213425925925926);
+        // a lapse sends the card to the first relearning step instead of the FSRS interval
+        assert_eq!(result.interval_raw, 10.0 / (24.0 * 60.0));
         assert_eq!(result.review_count, 4);
+        assert_eq!(result.phase, Phase::Relearning { step: 1 });
     }
 
     #[test]
     fn test_retention() {
+        let fsrs_only = LearningSteps {
+            learning: vec![],
+            relearning: vec![],
+        };
         let now = chrono::Utc::now();
         let initial_perf = ReviewedPerformance {
             last_reviewed_at: now - Duration::days(4),
@@ -223,12 +471,16 @@ mod tests {
             interval_days: 4,
             due_date: now + Duration::days(4),
             review_count: 4,
+            phase: Phase::Review,
         };
         let result = update_performance(
             Performance::Reviewed(initial_perf),
-            ReviewStatus::Fail,
+            ReviewStatus::Again,
             now,
             0.9,
+            &DEFAULT_PARAMETERS,
+            &fsrs_only,
+            &IntervalLimits::default(),
         )
         .unwrap();
         assert_eq!(result.interval_raw, 0.7213425925925926);
@@ -244,12 +496,16 @@ mod tests {
             interval_days: 4,
             due_date: now + Duration::days(4),
             review_count: 4,
+            phase: Phase::Review,
         };
         let result = update_performance(
             Performance::Reviewed(initial_perf),
-            ReviewStatus::Fail,
+            ReviewStatus::Again,
             now,
             0.6,
+            &DEFAULT_PARAMETERS,
+            &fsrs_only,
+            &IntervalLimits::default(),
         )
         .unwrap();
         assert_eq!(result.interval_raw, 19.46959490740741);
@@ -259,13 +515,26 @@ mod tests {
     /// Test that cards can recover from a "stuck" state after multiple failures.
     #[test]
     fn stability_fix_after_failing() {
+        // Without steps, so every review goes through FSRS.
+        let fsrs_only = LearningSteps {
+            learning: vec![],
+            relearning: vec![],
+        };
         let mut perf = Performance::default();
         let mut time_reviewed = chrono::Utc::now();
 
         // Simulate 4 consecutive failures - card enters "stuck" state
         for _ in 0..4 {
-            let reviewed_perf =
-                update_performance(perf, ReviewStatus::Fail, time_reviewed, 0.9).unwrap();
+            let reviewed_perf = update_performance(
+                perf,
+                ReviewStatus::Again,
+                time_reviewed,
+                0.9,
+                &DEFAULT_PARAMETERS,
+                &fsrs_only,
+                &IntervalLimits::default(),
+            )
+            .unwrap();
             time_reviewed = reviewed_perf.due_date;
             perf = Performance::Reviewed(reviewed_perf);
         }
@@ -288,8 +557,16 @@ mod tests {
 
         // Simulate 4 consecutive passes - card should recover
         for _ in 0..4 {
-            let reviewed_perf =
-                update_performance(perf, ReviewStatus::Pass, time_reviewed, 0.9).unwrap();
+            let reviewed_perf = update_performance(
+                perf,
+                ReviewStatus::Good,
+                time_reviewed,
+                0.9,
+                &DEFAULT_PARAMETERS,
+                &fsrs_only,
+                &IntervalLimits::default(),
+            )
+            .unwrap();
             time_reviewed = reviewed_perf.due_date;
             perf = Performance::Reviewed(reviewed_perf);
         }
@@ -309,4 +586,222 @@ mod tests {
             after_passes.interval_raw
         );
     }
+
+    #[test]
+    fn grades_order_intervals_for_mature_card() {
+        let now = chrono::Utc::now();
+        let perf = Performance::Reviewed(ReviewedPerformance {
+            last_reviewed_at: now - Duration::days(10),
+            stability: 10.0,
+            difficulty: 5.0,
+            interval_raw: 10.0,
+            interval_days: 10,
+            due_date: now,
+            review_count: 5,
+            phase: Phase::Review,
+        });
+
+        let previews = preview_intervals(
+            perf,
+            now,
+            0.9,
+            &DEFAULT_PARAMETERS,
+            &LearningSteps::default(),
+            &IntervalLimits::default(),
+        )
+        .unwrap();
+        let statuses: Vec<ReviewStatus> = previews.iter().map(|(status, _)| *status).collect();
+        assert_eq!(statuses, ReviewStatus::ALL.to_vec());
+
+        let intervals: Vec<f64> = previews.iter().map(|(_, interval)| *interval).collect();
+        assert!(intervals[0] < intervals[1]);
+        assert!(intervals[1] < intervals[2]);
+        assert!(intervals[2] < intervals[3]);
+    }
+
+    #[test]
+    fn easy_skips_learning_steps() {
+        let reviewed_at = chrono::Utc::now();
+
+        let good = update_performance(
+            Performance::New,
+            ReviewStatus::Good,
+            reviewed_at,
+            0.9,
+            &DEFAULT_PARAMETERS,
+            &LearningSteps::default(),
+            &IntervalLimits::default(),
+        )
+        .unwrap();
+        let easy = update_performance(
+            Performance::New,
+            ReviewStatus::Easy,
+            reviewed_at,
+            0.9,
+            &DEFAULT_PARAMETERS,
+            &LearningSteps::default(),
+            &IntervalLimits::default(),
+        )
+        .unwrap();
+        let hard = update_performance(
+            Performance::New,
+            ReviewStatus::Hard,
+            reviewed_at,
+            0.9,
+            &DEFAULT_PARAMETERS,
+            &LearningSteps::default(),
+            &IntervalLimits::default(),
+        )
+        .unwrap();
+
+        assert_eq!(good.interval_days, 0);
+        assert_eq!(hard.interval_days, 0);
+        assert!(easy.interval_days >= 1);
+        assert!(easy.stability > good.stability);
+    }
+
+    #[test]
+    fn lapse_enters_relearning_regardless_of_review_count() {
+        let now = chrono::Utc::now();
+        let mature = Performance::Reviewed(ReviewedPerformance {
+            last_reviewed_at: now - Duration::days(60),
+            stability: 60.0,
+            difficulty: 5.0,
+            interval_raw: 60.0,
+            interval_days: 60,
+            due_date: now,
+            review_count: 50,
+            phase: Phase::Review,
+        });
+        let steps = LearningSteps::default();
+
+        let lapsed = update_performance(
+            mature,
+            ReviewStatus::Again,
+            now,
+            0.9,
+            &DEFAULT_PARAMETERS,
+            &steps,
+            &IntervalLimits::default(),
+        )
+        .unwrap();
+        assert_eq!(lapsed.phase, Phase::Relearning { step: 1 });
+        assert_eq!(lapsed.due_date, now + Duration::minutes(10));
+
+        let relearned = update_performance(
+            Performance::Reviewed(lapsed),
+            ReviewStatus::Good,
+            lapsed.due_date,
+            0.9,
+            &DEFAULT_PARAMETERS,
+            &steps,
+            &IntervalLimits::default(),
+        )
+        .unwrap();
+        assert_eq!(relearned.phase, Phase::Review);
+        assert!(relearned.interval_raw >= 1.0);
+    }
+
+    #[test]
+    fn custom_learning_steps() {
+        let steps = LearningSteps {
+            learning: vec![
+                Duration::minutes(5),
+                Duration::minutes(30),
+                Duration::hours(2),
+            ],
+            relearning: vec![],
+        };
+        let mut perf = Performance::New;
+        let mut now = chrono::Utc::now();
+        let answer = |perf: &mut Performance, now: &mut chrono::DateTime<chrono::Utc>, status| {
+            let reviewed = update_performance(
+                *perf,
+                status,
+                *now,
+                0.9,
+                &DEFAULT_PARAMETERS,
+                &steps,
+                &IntervalLimits::default(),
+            )
+            .unwrap();
+            let wait = reviewed.due_date - *now;
+            *now = reviewed.due_date;
+            *perf = Performance::Reviewed(reviewed);
+            (reviewed.phase, wait)
+        };
+
+        assert_eq!(
+            answer(&mut perf, &mut now, ReviewStatus::Good),
+            (Phase::Learning { step: 1 }, Duration::minutes(5))
+        );
+        assert_eq!(
+            answer(&mut perf, &mut now, ReviewStatus::Good),
+            (Phase::Learning { step: 2 }, Duration::minutes(30))
+        );
+        assert_eq!(
+            answer(&mut perf, &mut now, ReviewStatus::Hard),
+            (Phase::Learning { step: 2 }, Duration::minutes(75))
+        );
+        assert_eq!(
+            answer(&mut perf, &mut now, ReviewStatus::Again),
+            (Phase::Learning { step: 1 }, Duration::minutes(5))
+        );
+        answer(&mut perf, &mut now, ReviewStatus::Good);
+        assert_eq!(
+            answer(&mut perf, &mut now, ReviewStatus::Good),
+            (Phase::Learning { step: 3 }, Duration::hours(2))
+        );
+        let (phase, _) = answer(&mut perf, &mut now, ReviewStatus::Good);
+        assert_eq!(phase, Phase::Review);
+    }
+
+    #[test]
+    fn rating_round_trips() {
+        for status in ReviewStatus::ALL {
+            assert_eq!(ReviewStatus::from_rating(status.rating()), Some(status));
+        }
+        assert_eq!(ReviewStatus::from_rating(0), None);
+        assert_eq!(ReviewStatus::from_rating(5), None);
+    }
+
+    #[test]
+    fn interval_limits_bound_fsrs_intervals() {
+        let fsrs_only = LearningSteps {
+            learning: vec![],
+            relearning: vec![],
+        };
+        let now = chrono::Utc::now();
+        let mature = Performance::Reviewed(ReviewedPerformance {
+            last_reviewed_at: now - Duration::days(60),
+            stability: 60.0,
+            difficulty: 5.0,
+            interval_raw: 60.0,
+            interval_days: 60,
+            due_date: now,
+            review_count: 10,
+            phase: Phase::Review,
+        });
+        let limits = IntervalLimits::new(30.0, 7.0).unwrap();
+        let review = |status| {
+            update_performance(
+                mature,
+                status,
+                now,
+                0.9,
+                &DEFAULT_PARAMETERS,
+                &fsrs_only,
+                &limits,
+            )
+            .unwrap()
+        };
+
+        let easy = review(ReviewStatus::Easy);
+        assert_eq!(easy.interval_raw, 30.0);
+        assert_eq!(easy.due_date, now + Duration::days(30));
+        assert_eq!(review(ReviewStatus::Again).interval_raw, 7.0);
+
+        assert!(IntervalLimits::new(0.0, 0.0).is_err());
+        assert!(IntervalLimits::new(10.0, 20.0).is_err());
+    }
 }
@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::fsrs::{IntervalLimits, Phase, ReviewedPerformance};
+use crate::utils::DayBoundary;
+
+/// Intervals shorter than this are never fuzzed.
+const MIN_FUZZ_DAYS: f64 = 2.5;
+
+/// `(start, end, factor)`: each day of the interval inside `start..end` widens the window by `factor` days.
+const FUZZ_RANGES: [(f64, f64, f64); 3] = [
+    (2.5, 7.0, 0.15),
+    (7.0, 20.0, 0.1),
+    (20.0, f64::INFINITY, 0.05),
+];
+
+/// A stable seed for one review of one card, so the same review always lands on the same day.
+pub fn fuzz_seed(card_hash: &str, review_count: usize) -> u64 {
+    // FNV-1a: unlike the std hasher it is guaranteed not to change between releases
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in card_hash.bytes().chain(review_count.to_le_bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Whole-day bounds an interval may be moved within, or `None` if it is too short to fuzz.
+pub fn fuzz_range(interval_days: f64) -> Option<(i64, i64)> {
+    if interval_days < MIN_FUZZ_DAYS {
+        return None;
+    }
+    let delta = 1.0
+        + FUZZ_RANGES
+            .iter()
+            .map(|(start, end, factor)| factor * (interval_days.min(*end) - start).max(0.0))
+            .sum::<f64>();
+    let min = ((interval_days - delta).round() as i64).max(2);
+    let max = ((interval_days + delta).round() as i64).max(min);
+    Some((min, max))
+}
+
+/// Spread `interval_days` over its fuzz range, picking a day from `seed`.
+pub fn fuzzed_interval(interval_days: f64, seed: u64) -> f64 {
+    match fuzz_range(interval_days) {
+        Some((min, max)) => pick(min, max, seed) as f64,
+        None => interval_days,
+    }
+}
+
+/// Like [`fuzzed_interval`], but prefer the least busy day in the fuzz range. Falls back to
+/// plain fuzz when part of the range lies beyond the days `load` knows about.
+pub fn balanced_interval(
+    interval_days: f64,
+    seed: u64,
+    reviewed_at: DateTime<Utc>,
+    load: &DueLoad,
+) -> f64 {
+    let Some((min, max)) = fuzz_range(interval_days) else {
+        return interval_days;
+    };
+    let fuzzed = pick(min, max, seed);
+    let counts: Option<Vec<(i64, usize)>> = (min..=max)
+        .map(|days| {
+            load.count(reviewed_at + Duration::days(days))
+                .map(|count| (days, count))
+        })
+        .collect();
+    let Some(counts) = counts else {
+        return fuzzed as f64;
+    };
+    counts
+        .into_iter()
+        .min_by_key(|(days, count)| (*count, (days - fuzzed).abs()))
+        .map_or(fuzzed, |(days, _)| days) as f64
+}
+
+/// Move a freshly scheduled review-phase card within its fuzz range, keeping it within
+/// `limits`; `lapsed` says whether the review was a lapse, so the minimum after a lapse holds.
+/// Learning steps keep their exact delays.
+pub fn fuzz_performance(
+    performance: &mut ReviewedPerformance,
+    seed: u64,
+    limits: &IntervalLimits,
+    lapsed: bool,
+    load: Option<&DueLoad>,
+) {
+    if performance.phase != Phase::Review {
+        return;
+    }
+    let reviewed_at = performance.last_reviewed_at;
+    let days = match load {
+        Some(load) => balanced_interval(performance.interval_raw, seed, reviewed_at, load),
+        None => fuzzed_interval(performance.interval_raw, seed),
+    };
+    let days = limits.clamp(days, lapsed);
+    if days == performance.interval_raw {
+        return;
+    }
+    performance.interval_raw = days;
+    performance.interval_days = days as usize;
+    performance.due_date = reviewed_at + Duration::seconds((days * 86_400.0).round() as i64);
+}
+
+fn pick(min: i64, max: i64, seed: u64) -> i64 {
+    let span = (max - min + 1) as u64;
+    min + (seed % span) as i64
+}
+
+/// Cards due on each upcoming study day, as counted by `CardStats::upcoming_week`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DueLoad {
+    day: DayBoundary,
+    counts: BTreeMap<NaiveDate, usize>,
+    last_day: Option<NaiveDate>,
+}
+
+impl DueLoad {
+    /// `upcoming_week` is keyed by `%Y-%m-%d` study days, starting today.
+    pub fn from_upcoming_week(
+        upcoming_week: &BTreeMap<String, usize>,
+        day: DayBoundary,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let counts = upcoming_week
+            .iter()
+            .filter_map(|(date, count)| {
+                NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .ok()
+                    .map(|date| (date, *count))
+            })
+            .collect();
+        // the week is counted even when some of its days have nothing due
+        let last_day = Some(day.study_day(now) + Duration::days(6));
+        Self {
+            day,
+            counts,
+            last_day,
+        }
+    }
+
+    /// Cards due on the study day of `due`, or `None` past the days this load covers.
+    pub fn count(&self, due: DateTime<Utc>) -> Option<usize> {
+        let date = self.day.study_day(due);
+        if self.last_day.is_none_or(|last| date > last) {
+            return None;
+        }
+        Some(self.counts.get(&date).copied().unwrap_or(0))
+    }
+
+    pub fn add(&mut self, due: DateTime<Utc>) {
+        *self.counts.entry(self.day.study_day(due)).or_insert(0) += 1;
+    }
+
+    pub fn remove(&mut self, due: DateTime<Utc>) {
+        if let Some(count) = self.counts.get_mut(&self.day.study_day(due)) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_intervals_are_not_fuzzed() {
+        assert_eq!(fuzz_range(1.0), None);
+        assert_eq!(fuzzed_interval(2.0, 42), 2.0);
+    }
+
+    #[test]
+    fn fuzz_is_deterministic_and_spreads_cards() {
+        let (min, max) = fuzz_range(30.0).unwrap();
+        assert!(min < 30 && max > 30);
+
+        let seed = fuzz_seed("abc", 3);
+        assert_eq!(seed, fuzz_seed("abc", 3));
+        assert_ne!(seed, fuzz_seed("abc", 4));
+        assert_eq!(fuzzed_interval(30.0, seed), fuzzed_interval(30.0, seed));
+
+        let days: std::collections::HashSet<i64> = (0..50)
+            .map(|i| fuzzed_interval(30.0, fuzz_seed(&format!("card-{i}"), 1)) as i64)
+            .collect();
+        assert!(days.len() > 3);
+        assert!(days.iter().all(|day| (min..=max).contains(day)));
+    }
+
+    #[test]
+    fn fuzz_stays_within_the_interval_limits() {
+        let now = Utc::now();
+        let limits = IntervalLimits::new(11.0, 10.0).unwrap();
+        let performance = ReviewedPerformance {
+            last_reviewed_at: now,
+            stability: 10.0,
+            difficulty: 5.0,
+            interval_raw: 10.0,
+            interval_days: 10,
+            due_date: now + Duration::days(10),
+            review_count: 4,
+            phase: Phase::Review,
+        };
+        // the quietest day in the fuzz range is below the minimum
+        let day = DayBoundary::default();
+        let (min, max) = fuzz_range(10.0).unwrap();
+        let upcoming_week: BTreeMap<String, usize> = (min..=max)
+            .map(|days| {
+                let date = day.study_day(now + Duration::days(days));
+                (date.format("%Y-%m-%d").to_string(), days as usize)
+            })
+            .collect();
+        let load = DueLoad::from_upcoming_week(&upcoming_week, day, now);
+
+        let fuzz = |i: usize, lapsed: bool, load: Option<&DueLoad>| {
+            let mut fuzzed = performance;
+            let seed = fuzz_seed(&format!("card-{i}"), 4);
+            fuzz_performance(&mut fuzzed, seed, &limits, lapsed, load);
+            fuzzed
+        };
+        for i in 0..50 {
+            for load in [None, Some(&load)] {
+                let fuzzed = fuzz(i, true, load);
+                assert!((10.0..=11.0).contains(&fuzzed.interval_raw));
+                assert!(fuzzed.due_date >= now + Duration::days(10));
+            }
+        }
+        // without a lapse only the maximum applies
+        assert!((0..50).any(|i| fuzz(i, false, None).interval_raw < 10.0));
+    }
+
+    #[test]
+    fn balancing_picks_the_lightest_day() {
+        let day = DayBoundary::default();
+        let now = Utc::now();
+        let (min, max) = fuzz_range(4.0).unwrap();
+        let mut upcoming_week = BTreeMap::new();
+        for days in min..=max {
+            let date = day.study_day(now + Duration::days(days));
+            upcoming_week.insert(date.format("%Y-%m-%d").to_string(), 10);
+        }
+        let quiet = day.study_day(now + Duration::days(max));
+        upcoming_week.insert(quiet.format("%Y-%m-%d").to_string(), 1);
+        let mut load = DueLoad::from_upcoming_week(&upcoming_week, day, now);
+
+        for i in 0..5 {
+            let seed = fuzz_seed(&format!("card-{i}"), 1);
+            assert_eq!(balanced_interval(4.0, seed, now, &load), max as f64);
+        }
+
+        // past the known week there is nothing to balance against
+        let seed = fuzz_seed("card", 1);
+        assert_eq!(
+            balanced_interval(40.0, seed, now, &load),
+            fuzzed_interval(40.0, seed)
+        );
+
+        for _ in 0..20 {
+            load.add(now + Duration::days(max));
+        }
+        assert_ne!(balanced_interval(4.0, seed, now, &load), max as f64);
+    }
+}
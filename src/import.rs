@@ -17,7 +17,7 @@ use crate::parser::get_hash;
 
 static TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<[^>]+>").unwrap());
 static CLOZE_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"(?s)\{\{c\d+::(.*?)(?:::(.*?))?\}\}").unwrap());
+    Lazy::new(|| Regex::new(r"(?s)\{\{c(\d+)::(.*?)(?:::(.*?))?\}\}").unwrap());
 
 #[derive(Clone)]
 struct DeckInfo {
@@ -34,6 +34,7 @@ enum ModelKind {
 #[derive(Clone, Debug)]
 struct CardRecord {
     deck_id: i64,
+    note_id: i64,
     model_id: i64,
     card_order: i64,
     fields: Vec<String>,
@@ -152,6 +153,7 @@ async fn load_cards(pool: &SqlitePool) -> Result<Vec<CardRecord>> {
         r#"
         SELECT
             cards.did  AS did,  -- deck id
+            cards.nid  AS nid,  -- note id
             cards.ord  AS ord,  -- card order (template ordinal)
             notes.mid  AS mid,  -- model (note type) id
             notes.flds AS flds  -- packed field values
@@ -165,6 +167,7 @@ async fn load_cards(pool: &SqlitePool) -> Result<Vec<CardRecord>> {
     let mut cards = Vec::with_capacity(rows.len());
     for row in rows {
         let deck_id: i64 = row.try_get("did")?;
+        let note_id: i64 = row.try_get("nid")?;
         let card_order: i64 = row.try_get("ord")?;
         let model_id: i64 = row.try_get("mid")?;
 
@@ -172,6 +175,7 @@ async fn load_cards(pool: &SqlitePool) -> Result<Vec<CardRecord>> {
         let fields_raw: String = row.try_get("flds")?;
         let card = CardRecord {
             deck_id,
+            note_id,
             model_id,
             card_order,
             fields: split_fields(&fields_raw),
@@ -193,6 +197,18 @@ fn build_exports(
     let mut num_duplicates = 0;
     let mut content_hashes: HashSet<String> = HashSet::new();
 
+    // notes with a reverse card alongside the forward one become a single `R:` card
+    let forward: HashSet<i64> = cards
+        .iter()
+        .filter(|card| card.card_order == 0)
+        .map(|card| card.note_id)
+        .collect();
+    let reversed: HashSet<i64> = cards
+        .iter()
+        .filter(|card| card.card_order == 1 && forward.contains(&card.note_id))
+        .map(|card| card.note_id)
+        .collect();
+
     let mut unexportable = 0;
     for card in cards {
         let Some(model) = models.get(&card.model_id) else {
@@ -203,6 +219,12 @@ fn build_exports(
             continue;
         };
         let entry = match model {
+            ModelKind::Basic if reversed.contains(&card.note_id) => {
+                if card.card_order != 0 {
+                    continue;
+                }
+                reversible_entry(&card.fields)
+            }
             ModelKind::Basic => basic_entry(&card.fields, card.card_order),
             ModelKind::Cloze => cloze_entry(&card.fields),
         };
@@ -352,6 +374,16 @@ fn basic_entry(fields: &[String], ord: i64) -> Option<String> {
     Some(entry)
 }
 
+fn reversible_entry(fields: &[String]) -> Option<String> {
+    if fields.len() < 2 {
+        return None;
+    }
+    let mut entry = format_section("R", &fields[0])?;
+    entry.push_str(&format_section("A", &fields[1])?);
+    entry.push('\n');
+    Some(entry)
+}
+
 fn cloze_entry(fields: &[String]) -> Option<String> {
     let text = fields.first()?;
     let converted = convert_cloze(text);
@@ -376,8 +408,13 @@ fn format_section(label: &str, value: &str) -> Option<String> {
 fn convert_cloze(text: &str) -> String {
     CLOZE_RE
         .replace_all(text, |caps: &regex::Captures| {
-            let inner = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-            format!("[{}]", inner.trim())
+            let group = &caps[1];
+            // a bare ` | ` would start a hint
+            let answer = caps[2].trim().replace('|', "\\|");
+            match caps.get(3).map(|hint| hint.as_str().trim()) {
+                Some(hint) if !hint.is_empty() => format!("[c{group}: {answer} | {hint}]"),
+                _ => format!("[c{group}: {answer}]"),
+            }
         })
         .into_owned()
 }
@@ -406,10 +443,44 @@ mod tests {
         assert!(basic_entry(&["Only".into()], 0).is_none());
     }
 
+    #[test]
+    fn reversed_notes_become_one_reversible_card() {
+        let card = |note_id, card_order| CardRecord {
+            deck_id: 1,
+            note_id,
+            model_id: 7,
+            card_order,
+            fields: vec!["hund".into(), "dog".into()],
+        };
+        let models = HashMap::from([(7, ModelKind::Basic)]);
+        let mut optional_reverse = card(2, 1);
+        optional_reverse.fields = vec!["katt".into(), "cat".into()];
+        let exports = build_exports(vec![card(1, 0), card(1, 1), optional_reverse], &models);
+        assert_eq!(
+            exports[&1],
+            vec![
+                "R: hund\nA: dog\n\n".to_string(),
+                "Q: cat\nA: katt\n\n".to_string()
+            ]
+        );
+    }
+
     #[test]
     fn convert_cloze_rewrites_all_cloze_blocks() {
-        let text = "Capital {{c1::Tokyo}} and {{c2::Kyoto::hint}}";
-        assert_eq!(convert_cloze(text), "Capital [Tokyo] and [Kyoto]");
+        let text = "Capital {{c1::Tokyo}} and {{c2::Kyoto::hint}} and {{c1::Osaka}}";
+        assert_eq!(
+            convert_cloze(text),
+            "Capital [c1: Tokyo] and [c2: Kyoto | hint] and [c1: Osaka]"
+        );
+        assert_eq!(
+            convert_cloze("Run {{c1::ls | grep foo::shell}}"),
+            "Run [c1: ls \\| grep foo | shell]"
+        );
+
+        let converted = format!("C: {}", convert_cloze(text));
+        let cards =
+            crate::parser::content_to_cards(Path::new("anki.md"), &converted, 0, 1).unwrap();
+        assert_eq!(cards.len(), 2);
     }
 
     #[test]
@@ -2,13 +2,17 @@ pub mod card;
 pub mod check_version;
 pub mod cloze_utils;
 pub mod commands;
+pub mod config;
 pub mod crud;
 pub mod fsrs;
+pub mod fuzz;
 pub mod import;
 pub mod llm;
 pub mod notes;
+pub mod order;
 pub mod palette;
 pub mod parser;
+pub mod simulation;
 pub mod stats;
 pub mod tui;
 pub mod utils;
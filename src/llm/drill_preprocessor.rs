@@ -182,7 +182,10 @@ pub async fn rephrase_basic_questions_with_client(
     let cards_to_rephrase: Vec<_> = cards
         .iter()
         .filter_map(|card| {
-            if let CardContent::Basic { question, answer } = &card.content {
+            if let CardContent::Basic {
+                question, answer, ..
+            } = &card.content
+            {
                 Some((card.card_hash.clone(), question.clone(), answer.clone()))
             } else {
                 None
@@ -257,6 +260,7 @@ pub async fn resolve_missing_clozes_with_client(
             if let CardContent::Cloze {
                 text,
                 cloze_range: None,
+                ..
             } = &card.content
             {
                 Some((card.card_hash.clone(), text.clone()))
@@ -77,6 +77,7 @@ pub fn cloze_user_prompt(cards: &[Card], total_needing: usize) -> Option<String>
         if let CardContent::Cloze {
             text,
             cloze_range: None,
+            ..
         } = &card.content
             && sample_text.is_none()
         {
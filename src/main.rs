@@ -1,14 +1,24 @@
 use std::path::PathBuf;
 
 use anyhow::{Context, Result, bail};
+use chrono::NaiveDate;
 use clap::{Parser, Subcommand, ValueHint};
 
 use repeater::commands::{
+    card::{self, CardAction},
     check, create,
-    drill::{self, DrillOptions},
+    drill::{self, DrillMode, DrillOptions},
+    ids, leeches, optimize,
+    reschedule::{self, RescheduleOptions},
+    simulate, suspend,
 };
+use repeater::config::Config;
 use repeater::crud::DB;
+use repeater::crud::cards::ReviewOptions;
 use repeater::llm::client;
+use repeater::order::DrillOrder;
+use repeater::parser::TagFilter;
+use repeater::simulation::SimulationOptions;
 use repeater::{import, llm, palette::Palette};
 
 #[derive(Parser, Debug)]
@@ -48,12 +58,32 @@ enum Command {
         /// Rephrase  card questions via the LLM helper before the session starts.
         #[arg(long = "rephrase", default_value_t = false)]
         rephrase_questions: bool,
-        /// Randomize the order of cards in the drill session.
-        #[arg(long, default_value_t = true)]
+        /// Order to show cards in: random, due, retrievability, file, by-file, interleave-new, or new-last.
+        /// Defaults to `drill_order` in repeater.toml, or random.
+        #[arg(long, value_name = "ORDER")]
+        order: Option<DrillOrder>,
+        /// Shorthand for `--order random`.
+        #[arg(long, default_value_t = false, conflicts_with = "order")]
         shuffle: bool,
         /// Goal retention FSRS should use, this is your target probability of recalling a card at review time.
-        #[arg(long, default_value_t = 0.9)]
-        retention: f32,
+        /// Defaults to `retention` in repeater.toml, or 0.9.
+        #[arg(long)]
+        retention: Option<f32>,
+        /// Only drill cards matching this text, path:line, or hash prefix.
+        #[arg(long = "match", value_name = "QUERY")]
+        filter: Option<String>,
+        /// Only drill cards with this tag, or a tag nested under it. Repeat to allow several tags.
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Leave out cards with this tag, or a tag nested under it. Can be repeated.
+        #[arg(long = "exclude-tag", value_name = "TAG")]
+        exclude_tags: Vec<String>,
+        /// Go through every card, due or not, without changing when any of them is due next.
+        #[arg(long, default_value_t = false, conflicts_with_all = ["new_card_limit", "retention"])]
+        cram: bool,
+        /// With --cram, keep the grades in the review log, marked as cram reviews.
+        #[arg(long, default_value_t = false, requires = "cram")]
+        log: bool,
         /// Drill cards from Apple Notes instead of local files (macOS only).
         #[arg(long, default_value_t = false, conflicts_with = "paths")]
         apple_notes: bool,
@@ -73,6 +103,15 @@ enum Command {
         /// Check cards from Apple Notes instead of local files (macOS only).
         #[arg(long, default_value_t = false, conflicts_with = "paths")]
         apple_notes: bool,
+        /// Only count cards with this tag, or a tag nested under it. Repeat to allow several tags.
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Leave out cards with this tag, or a tag nested under it. Can be repeated.
+        #[arg(long = "exclude-tag", value_name = "TAG")]
+        exclude_tags: Vec<String>,
+        /// Add a retention-vs-workload chart to the dashboard (simulates the collection)
+        #[arg(long, default_value_t = false, conflicts_with = "plain")]
+        workload: bool,
     },
     /// Create or append to a card
     Create {
@@ -89,6 +128,114 @@ enum Command {
         #[arg(value_name = "PATH", value_hint = ValueHint::AnyPath)]
         export_path: PathBuf,
     },
+    /// Train personalized FSRS parameters from your review history
+    Optimize {
+        /// Discard the personalized parameters and go back to the FSRS defaults
+        #[arg(long, default_value_t = false)]
+        reset: bool,
+    },
+    /// Keep matching cards out of drills until they are unsuspended
+    Suspend {
+        /// Text from the card, its path:line, or a prefix of its hash
+        query: String,
+        #[arg(
+            value_name = "PATHS",
+            num_args = 0..,
+            default_value = ".",
+            value_hint = ValueHint::AnyPath
+        )]
+        paths: Vec<PathBuf>,
+        /// Change every matching card when the query matches more than one
+        #[arg(long)]
+        all: bool,
+    },
+    /// Return suspended or buried matching cards to drills
+    Unsuspend {
+        /// Text from the card, its path:line, or a prefix of its hash
+        query: String,
+        #[arg(
+            value_name = "PATHS",
+            num_args = 0..,
+            default_value = ".",
+            value_hint = ValueHint::AnyPath
+        )]
+        paths: Vec<PathBuf>,
+        /// Change every matching card when the query matches more than one
+        #[arg(long)]
+        all: bool,
+    },
+    /// Reset progress or set the due date of matching cards
+    Card {
+        #[command(subcommand)]
+        action: CardCommand,
+    },
+    /// Show which cards have stable ids, or add ids to cards without one
+    Ids {
+        #[arg(
+            value_name = "PATHS",
+            num_args = 0..,
+            default_value = ".",
+            value_hint = ValueHint::AnyPath
+        )]
+        paths: Vec<PathBuf>,
+        /// Write an id comment into every card without one, keeping its history
+        #[arg(long, default_value_t = false)]
+        assign: bool,
+    },
+    /// List cards that keep lapsing
+    Leeches {
+        #[arg(
+            value_name = "PATHS",
+            num_args = 0..,
+            default_value = ".",
+            value_hint = ValueHint::AnyPath
+        )]
+        paths: Vec<PathBuf>,
+    },
+    /// Spread out a backlog of overdue cards, or skip time spent away
+    Reschedule {
+        #[arg(
+            value_name = "PATHS",
+            num_args = 0..,
+            default_value = ".",
+            value_hint = ValueHint::AnyPath
+        )]
+        paths: Vec<PathBuf>,
+        /// Spread cards that were due before today over this many days, least remembered first.
+        #[arg(long, value_name = "DAYS")]
+        spread: Option<usize>,
+        /// First day away (YYYY-MM-DD). Time away doesn't count as forgetting, and cards due while away are pushed back.
+        #[arg(long, value_name = "DATE", requires = "away_until")]
+        away_from: Option<NaiveDate>,
+        /// Last day away (YYYY-MM-DD), inclusive.
+        #[arg(long, value_name = "DATE", requires = "away_from")]
+        away_until: Option<NaiveDate>,
+        /// Show what would change without saving it.
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Simulate future workload to pick a retention target
+    Simulate {
+        #[arg(
+            value_name = "PATHS",
+            num_args = 0..,
+            default_value = ".",
+            value_hint = ValueHint::AnyPath
+        )]
+        paths: Vec<PathBuf>,
+        /// Number of days to simulate.
+        #[arg(long, default_value_t = 365)]
+        days: usize,
+        /// New cards introduced per day during the simulation.
+        #[arg(long, value_name = "COUNT", default_value_t = 20)]
+        new_cards_per_day: usize,
+        /// Daily study budget in minutes; the recommendation will fit within it.
+        #[arg(long, value_name = "MINUTES")]
+        minutes: Option<f64>,
+        /// Simulate cards from Apple Notes instead of local files (macOS only).
+        #[arg(long, default_value_t = false, conflicts_with = "paths")]
+        apple_notes: bool,
+    },
     /// Manage LLM helper settings
     Llm {
         /// Store a new API key in the local auth file
@@ -103,6 +250,67 @@ enum Command {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum CardCommand {
+    /// Send a card back to new and clear its lapses and review history
+    Reset {
+        /// The card's path:line, or its full hash or id
+        query: String,
+        #[arg(
+            value_name = "PATHS",
+            num_args = 0..,
+            default_value = ".",
+            value_hint = ValueHint::AnyPath
+        )]
+        paths: Vec<PathBuf>,
+        /// List the matching cards without changing them.
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// Don't ask for confirmation before changing the cards.
+        #[arg(long, short = 'y', default_value_t = false)]
+        yes: bool,
+    },
+    /// Send a card back to new, keeping its review history
+    Forget {
+        /// The card's path:line, or its full hash or id
+        query: String,
+        #[arg(
+            value_name = "PATHS",
+            num_args = 0..,
+            default_value = ".",
+            value_hint = ValueHint::AnyPath
+        )]
+        paths: Vec<PathBuf>,
+        /// List the matching cards without changing them.
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// Don't ask for confirmation before changing the cards.
+        #[arg(long, short = 'y', default_value_t = false)]
+        yes: bool,
+    },
+    /// Make matching cards due on a given day
+    SetDue {
+        /// Text from the card, its path:line, or a prefix of its hash
+        query: String,
+        /// YYYY-MM-DD, today, tomorrow, or +N days from today
+        #[arg(value_name = "DATE")]
+        date: String,
+        #[arg(
+            value_name = "PATHS",
+            num_args = 0..,
+            default_value = ".",
+            value_hint = ValueHint::AnyPath
+        )]
+        paths: Vec<PathBuf>,
+        /// List the matching cards without changing them.
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// Don't ask for confirmation before changing the cards.
+        #[arg(long, short = 'y', default_value_t = false)]
+        yes: bool,
+    },
+}
+
 #[tokio::main]
 async fn main() {
     if let Err(err) = run_cli().await {
@@ -121,22 +329,59 @@ async fn run_cli() -> Result<()> {
             card_limit,
             new_card_limit,
             rephrase_questions,
+            order,
             shuffle,
             retention,
+            filter,
+            tags,
+            exclude_tags,
+            cram,
+            log,
             apple_notes,
         } => {
+            let config = Config::load_for(&paths)?;
             drill::run(&db, DrillOptions {
                 paths,
                 card_limit,
                 new_card_limit,
                 rephrase_questions,
-                shuffle,
-                retention,
+                order: if shuffle {
+                    DrillOrder::Random
+                } else {
+                    order.unwrap_or(config.drill_order)
+                },
+                new_card_spacing: config.new_card_spacing,
+                review: ReviewOptions {
+                    retention: retention.unwrap_or(config.retention),
+                    steps: config.steps,
+                    intervals: config.intervals,
+                    leech: config.leech,
+                    fuzz: config.fuzz,
+                    load: None,
+                },
+                day: config.day,
+                daily_limits: config.daily_limits,
+                load_balance: config.load_balance,
+                filter,
+                tags: TagFilter::new(&tags, &exclude_tags)?,
+                mode: if cram {
+                    DrillMode::Cram { log }
+                } else {
+                    DrillMode::Schedule
+                },
                 apple_notes,
             }).await?;
         }
-        Command::Check { paths, plain, apple_notes } => {
-            let _ = check::run(&db, paths, plain, apple_notes).await?;
+        Command::Check {
+            paths,
+            plain,
+            apple_notes,
+            tags,
+            exclude_tags,
+            workload,
+        } => {
+            let tags = TagFilter::new(&tags, &exclude_tags)?;
+            let _ = check::run(&db, paths, plain, apple_notes, &tags, workload).await?;
         }
         Command::Create { path } => {
             create::run(&db, path).await?;
@@ -148,6 +393,83 @@ async fn run_cli() -> Result<()> {
             import::run(&db, &anki_path, &export_path)
                 .await.with_context(|| "Importing from Anki is a work in progress, please report issues on https://github.com/shaankhosla/repeater")?
         },
+        Command::Optimize { reset } => {
+            optimize::run(&db, reset).await?;
+        }
+        Command::Suspend { query, paths, all } => {
+            suspend::run(&db, &query, paths, true, all).await?;
+        }
+        Command::Unsuspend { query, paths, all } => {
+            suspend::run(&db, &query, paths, false, all).await?;
+        }
+        Command::Ids { paths, assign } => {
+            ids::run(&db, paths, assign).await?;
+        }
+        Command::Card { action } => {
+            let (action, query, paths, dry_run, yes) = match action {
+                CardCommand::Reset {
+                    query,
+                    paths,
+                    dry_run,
+                    yes,
+                } => (CardAction::Reset, query, paths, dry_run, yes),
+                CardCommand::Forget {
+                    query,
+                    paths,
+                    dry_run,
+                    yes,
+                } => (CardAction::Forget, query, paths, dry_run, yes),
+                CardCommand::SetDue {
+                    query,
+                    date,
+                    paths,
+                    dry_run,
+                    yes,
+                } => {
+                    let day = Config::load_for(&paths)?.day;
+                    let date = card::parse_due_day(&date, day, chrono::Utc::now())?;
+                    (CardAction::SetDue(date), query, paths, dry_run, yes)
+                }
+            };
+            let day = Config::load_for(&paths)?.day;
+            card::run(&db, action, &query, paths, day, dry_run, yes).await?;
+        }
+        Command::Leeches { paths } => {
+            leeches::run(&db, paths).await?;
+        }
+        Command::Reschedule {
+            paths,
+            spread,
+            away_from,
+            away_until,
+            dry_run,
+        } => {
+            let config = Config::load_for(&paths)?;
+            reschedule::run(&db, RescheduleOptions {
+                paths,
+                spread,
+                away: away_from.zip(away_until),
+                day: config.day,
+                dry_run,
+            }).await?;
+        }
+        Command::Simulate {
+            paths,
+            days,
+            new_cards_per_day,
+            minutes,
+            apple_notes,
+        } => {
+            let config = Config::load_for(&paths)?;
+            let options = SimulationOptions {
+                days,
+                new_cards_per_day,
+                minutes_per_day: minutes,
+                steps: config.steps,
+                intervals: config.intervals,
+            };
+            simulate::run(&db, paths, options, apple_notes).await?;
+        }
         Command::Llm { set, clear, test } => handle_llm_command(set, clear, test).await?,
     }
 
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::{Error, bail};
+use chrono::{DateTime, Utc};
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+
+use crate::card::Card;
+use crate::crud::stats::CardStatsRow;
+
+/// New cards shown after this many reviews with [`DrillOrder::InterleaveNew`], unless configured.
+pub const DEFAULT_NEW_CARD_SPACING: usize = 5;
+
+/// The order cards are shown in during a drill.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DrillOrder {
+    /// Shuffle every card.
+    #[default]
+    Random,
+    /// Most overdue first, then new cards.
+    Due,
+    /// The cards you are least likely to remember first, then new cards.
+    Retrievability,
+    /// File by file, top to bottom.
+    File,
+    /// Cards from the same file together, files in due order.
+    ByFile,
+    /// Reviews in due order with a new card after every few of them.
+    InterleaveNew,
+    /// Reviews in due order, then new cards.
+    NewLast,
+}
+
+impl DrillOrder {
+    pub const ALL: [DrillOrder; 7] = [
+        DrillOrder::Random,
+        DrillOrder::Due,
+        DrillOrder::Retrievability,
+        DrillOrder::File,
+        DrillOrder::ByFile,
+        DrillOrder::InterleaveNew,
+        DrillOrder::NewLast,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DrillOrder::Random => "random",
+            DrillOrder::Due => "due",
+            DrillOrder::Retrievability => "retrievability",
+            DrillOrder::File => "file",
+            DrillOrder::ByFile => "by-file",
+            DrillOrder::InterleaveNew => "interleave-new",
+            DrillOrder::NewLast => "new-last",
+        }
+    }
+}
+
+impl FromStr for DrillOrder {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match DrillOrder::ALL.iter().find(|order| order.as_str() == value) {
+            Some(order) => Ok(*order),
+            None => {
+                let names: Vec<&str> = DrillOrder::ALL.iter().map(DrillOrder::as_str).collect();
+                bail!("unknown order '{value}'; use one of {}", names.join(", "))
+            }
+        }
+    }
+}
+
+/// Everything [`order_cards`] needs besides the cards.
+pub struct OrderContext<'a> {
+    /// Scheduling state by card hash; cards without a reviewed row count as new.
+    pub rows: &'a HashMap<String, CardStatsRow>,
+    pub decay: f32,
+    pub now: DateTime<Utc>,
+    /// Reviews between new cards for [`DrillOrder::InterleaveNew`].
+    pub new_card_spacing: usize,
+}
+
+/// Reorder `cards`, which arrive in due order as returned by `DB::due_today`.
+pub fn order_cards(cards: &mut Vec<Card>, order: DrillOrder, context: &OrderContext) {
+    match order {
+        DrillOrder::Random => cards.shuffle(&mut rand::rng()),
+        DrillOrder::Due => {}
+        DrillOrder::Retrievability => {
+            let retrievability = |card: &Card| {
+                context
+                    .rows
+                    .get(&card.card_hash)
+                    .and_then(|row| row.retrievability(context.now, context.decay))
+                    .unwrap_or(f32::INFINITY)
+            };
+            cards.sort_by(|a, b| retrievability(a).total_cmp(&retrievability(b)));
+        }
+        DrillOrder::File => cards.sort_by(|a, b| {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.file_card_range.cmp(&b.file_card_range))
+        }),
+        DrillOrder::ByFile => {
+            let mut rank = HashMap::new();
+            for card in cards.iter() {
+                let next = rank.len();
+                rank.entry(card.file_path.clone()).or_insert(next);
+            }
+            cards.sort_by_key(|card| rank[&card.file_path]);
+        }
+        DrillOrder::InterleaveNew => {
+            let (new, reviews) = split_new(cards, context);
+            let spacing = context.new_card_spacing.max(1);
+            let mut new = new.into_iter();
+            for (idx, card) in reviews.into_iter().enumerate() {
+                cards.push(card);
+                if (idx + 1) % spacing == 0
+                    && let Some(card) = new.next()
+                {
+                    cards.push(card);
+                }
+            }
+            cards.extend(new);
+        }
+        DrillOrder::NewLast => {
+            let (new, reviews) = split_new(cards, context);
+            cards.extend(reviews);
+            cards.extend(new);
+        }
+    }
+}
+
+/// Drain `cards` into new cards and reviews, keeping their order.
+fn split_new(cards: &mut Vec<Card>, context: &OrderContext) -> (Vec<Card>, Vec<Card>) {
+    cards.drain(..).partition(|card| {
+        context
+            .rows
+            .get(&card.card_hash)
+            .is_none_or(|row| row.review_count == 0)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::Duration;
+    use fsrs::FSRS6_DEFAULT_DECAY;
+
+    use super::*;
+    use crate::parser::content_to_card;
+
+    fn card(file: &str, line: usize) -> Card {
+        let content = format!("Q: {file} {line}?\nA: answer");
+        content_to_card(&PathBuf::from(file), &content, line, line + 1).unwrap()
+    }
+
+    fn reviewed(card: &Card, stability: f64, now: DateTime<Utc>) -> (String, CardStatsRow) {
+        let row = CardStatsRow {
+            card_hash: card.card_hash.clone(),
+            review_count: 2,
+            due_date: Some(now),
+            interval_raw: Some(stability),
+            difficulty: Some(5.0),
+            stability: Some(stability),
+            last_reviewed_at: Some(now - Duration::days(10)),
+            suspended: false,
+            buried_until: None,
+            phase: "review".into(),
+        };
+        (card.card_hash.clone(), row)
+    }
+
+    fn lines(cards: &[Card]) -> Vec<(String, usize)> {
+        cards
+            .iter()
+            .map(|card| (card.file_path.display().to_string(), card.file_card_range.0))
+            .collect()
+    }
+
+    #[test]
+    fn parses_order_names() {
+        for order in DrillOrder::ALL {
+            assert_eq!(order.as_str().parse::<DrillOrder>().unwrap(), order);
+        }
+        assert!("sideways".parse::<DrillOrder>().is_err());
+    }
+
+    #[test]
+    fn orders_by_file_and_retrievability() {
+        let now = Utc::now();
+        let due = vec![
+            card("b.md", 1),
+            card("a.md", 5),
+            card("b.md", 9),
+            card("a.md", 1),
+        ];
+        let rows: HashMap<String, CardStatsRow> = [
+            reviewed(&due[0], 50.0, now),
+            reviewed(&due[1], 2.0, now),
+            reviewed(&due[2], 10.0, now),
+        ]
+        .into_iter()
+        .collect();
+        let context = OrderContext {
+            rows: &rows,
+            decay: FSRS6_DEFAULT_DECAY,
+            now,
+            new_card_spacing: 2,
+        };
+
+        let mut cards = due.clone();
+        order_cards(&mut cards, DrillOrder::File, &context);
+        assert_eq!(
+            lines(&cards),
+            vec![
+                ("a.md".into(), 1),
+                ("a.md".into(), 5),
+                ("b.md".into(), 1),
+                ("b.md".into(), 9)
+            ]
+        );
+
+        let mut cards = due.clone();
+        order_cards(&mut cards, DrillOrder::ByFile, &context);
+        assert_eq!(
+            lines(&cards),
+            vec![
+                ("b.md".into(), 1),
+                ("b.md".into(), 9),
+                ("a.md".into(), 5),
+                ("a.md".into(), 1)
+            ]
+        );
+
+        let mut cards = due.clone();
+        order_cards(&mut cards, DrillOrder::Retrievability, &context);
+        assert_eq!(
+            lines(&cards),
+            vec![
+                ("a.md".into(), 5),
+                ("b.md".into(), 9),
+                ("b.md".into(), 1),
+                ("a.md".into(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn places_new_cards_between_or_after_reviews() {
+        let now = Utc::now();
+        let due: Vec<Card> = (1..=7).map(|line| card("deck.md", line)).collect();
+        // lines 1, 3 and 5 are new
+        let rows: HashMap<String, CardStatsRow> = [&due[1], &due[3], &due[5], &due[6]]
+            .into_iter()
+            .map(|card| reviewed(card, 5.0, now))
+            .collect();
+        let context = OrderContext {
+            rows: &rows,
+            decay: FSRS6_DEFAULT_DECAY,
+            now,
+            new_card_spacing: 2,
+        };
+        let order = |cards: &[Card]| -> Vec<usize> {
+            cards.iter().map(|card| card.file_card_range.0).collect()
+        };
+
+        let mut cards = due.clone();
+        order_cards(&mut cards, DrillOrder::InterleaveNew, &context);
+        assert_eq!(order(&cards), vec![2, 4, 1, 6, 7, 3, 5]);
+
+        let mut cards = due.clone();
+        order_cards(&mut cards, DrillOrder::NewLast, &context);
+        assert_eq!(order(&cards), vec![2, 4, 6, 7, 1, 3, 5]);
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::HashSet;
+
+use anyhow::{Result, bail};
+
+// A stable ID is an HTML comment inside the card, e.g. `<!-- id: 7f3a -->`.
+// Markdown renderers hide it, and it keeps the card's history attached when
+// its wording changes.
+
+const OPEN: &str = "<!--";
+const CLOSE: &str = "-->";
+const KEY: &str = "id:";
+
+/// Length of the IDs written by [`new_card_id`].
+const NEW_ID_LEN: usize = 8;
+
+/// The `<!-- id: ... -->` comment for `id`.
+pub fn format_card_id(id: &str) -> String {
+    format!("{OPEN} {KEY} {id} {CLOSE}")
+}
+
+/// Split the ID comment out of a card, returning the rest of its text and the ID, if any.
+/// Comments that aren't IDs are left alone.
+pub fn strip_card_id(contents: &str) -> Result<(String, Option<String>)> {
+    let mut rest = String::with_capacity(contents.len());
+    let mut id = None;
+    let mut remaining = contents;
+    while let Some(start) = remaining.find(OPEN) {
+        let after_open = &remaining[start + OPEN.len()..];
+        let Some(end) = after_open.find(CLOSE) else {
+            break;
+        };
+        let Some(value) = after_open[..end].trim().strip_prefix(KEY) else {
+            rest.push_str(&remaining[..start + OPEN.len() + end + CLOSE.len()]);
+            remaining = &after_open[end + CLOSE.len()..];
+            continue;
+        };
+        let value = value.trim();
+        if !is_valid_id(value) {
+            bail!("Invalid card id '{value}': use letters, digits, '-' or '_'");
+        }
+        if let Some(previous) = &id
+            && previous != value
+        {
+            bail!("Card has two ids: '{previous}' and '{value}'");
+        }
+        id = Some(value.to_string());
+        rest.push_str(remaining[..start].trim_end_matches([' ', '\t']));
+        remaining = &after_open[end + CLOSE.len()..];
+    }
+    rest.push_str(remaining);
+    Ok((rest, id))
+}
+
+/// A random ID that isn't in `taken`.
+pub fn new_card_id(taken: &HashSet<String>) -> String {
+    loop {
+        let id = format!("{:016x}", rand::random::<u64>())[..NEW_ID_LEN].to_string();
+        if !taken.contains(&id) {
+            return id;
+        }
+    }
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_the_id_comment() {
+        let (rest, id) = strip_card_id("Q: Capital?\nA: Tokyo\n<!-- id: 7f3a -->\n").unwrap();
+        assert_eq!(rest, "Q: Capital?\nA: Tokyo\n\n");
+        assert_eq!(id.as_deref(), Some("7f3a"));
+
+        let (rest, id) = strip_card_id("Capital::Tokyo <!--id:7f3a-->").unwrap();
+        assert_eq!(rest, "Capital::Tokyo");
+        assert_eq!(id.as_deref(), Some("7f3a"));
+
+        let (rest, id) = strip_card_id("A: Tokyo <!-- a note -->").unwrap();
+        assert_eq!(rest, "A: Tokyo <!-- a note -->");
+        assert_eq!(id, None);
+
+        assert!(strip_card_id("<!-- id: two words -->").is_err());
+        assert!(strip_card_id("<!-- id: a -->\n<!-- id: b -->").is_err());
+    }
+
+    #[test]
+    fn new_ids_round_trip() {
+        let taken = HashSet::from(["7f3a".to_string()]);
+        let id = new_card_id(&taken);
+        assert_eq!(id.len(), NEW_ID_LEN);
+        let (_, parsed) = strip_card_id(&format_card_id(&id)).unwrap();
+        assert_eq!(parsed, Some(id));
+    }
+}
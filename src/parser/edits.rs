@@ -0,0 +1,182 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::card::Card;
+use crate::crud::DB;
+use crate::crud::cards::StoredCard;
+
+/// How alike two card texts must be, from 0 to 1, for one to count as an edit of the other.
+pub const EDIT_SIMILARITY: f64 = 0.6;
+
+/// How far, in lines, an edited card may have moved within its file.
+const MAX_LINE_SHIFT: i64 = 10;
+
+/// A card whose hash changed because it was reworded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CardEdit {
+    pub from: String,
+    pub to: String,
+    /// `path:line` of the reworded card.
+    pub location: String,
+    pub similarity: f64,
+}
+
+/// Move history from cards that disappeared since the last pass over `files` to new cards
+/// that look like edits of them: in the same file, near the same line, with similar text.
+/// Cards that disappeared without a match lose their location, so a card deleted long ago
+/// can't be mistaken for a later one. Must run before the new cards are registered, since
+/// history never overwrites a known card.
+pub async fn carry_over_edits(
+    db: &DB,
+    files: &[PathBuf],
+    cards: &HashMap<String, Card>,
+) -> Result<Vec<CardEdit>> {
+    let mut by_file: HashMap<&PathBuf, Vec<&Card>> = HashMap::new();
+    for card in cards.values() {
+        by_file.entry(&card.file_path).or_default().push(card);
+    }
+
+    let mut edits = Vec::new();
+    for path in files {
+        let file_cards = by_file.remove(path).unwrap_or_default();
+        let gone: Vec<StoredCard> = db
+            .cards_in_file(path)
+            .await?
+            .into_iter()
+            .filter(|stored| !cards.contains_key(&stored.card_hash))
+            .collect();
+        if gone.is_empty() {
+            continue;
+        }
+        let mut new = Vec::new();
+        for card in file_cards {
+            if !db.card_exists(card).await? {
+                new.push(card);
+            }
+        }
+        let file_edits = match_edits(&gone, &new);
+        let moves: Vec<(String, String)> = file_edits
+            .iter()
+            .map(|edit| (edit.from.clone(), edit.to.clone()))
+            .collect();
+        db.move_card_history(&moves).await?;
+        let unmatched: Vec<String> = gone
+            .into_iter()
+            .map(|stored| stored.card_hash)
+            .filter(|hash| !file_edits.iter().any(|edit| &edit.from == hash))
+            .collect();
+        db.clear_card_locations(&unmatched).await?;
+        edits.extend(file_edits);
+    }
+    edits.sort_by(|a, b| a.location.cmp(&b.location));
+    Ok(edits)
+}
+
+/// Pair disappeared cards with new cards from the same file, most similar pairs first.
+pub fn match_edits(gone: &[StoredCard], new: &[&Card]) -> Vec<CardEdit> {
+    let mut candidates = Vec::new();
+    for stored in gone {
+        for card in new {
+            let shift = (stored.file_line - card.file_card_range.0 as i64).abs();
+            if shift > MAX_LINE_SHIFT {
+                continue;
+            }
+            let similarity = similarity(&stored.content, &card.comparable_text());
+            if similarity >= EDIT_SIMILARITY {
+                candidates.push((similarity, shift, stored, *card));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+
+    let mut used_old = HashSet::new();
+    let mut used_new = HashSet::new();
+    let mut edits = Vec::new();
+    for (similarity, _, stored, card) in candidates {
+        if used_old.contains(&stored.card_hash) || used_new.contains(&card.card_hash) {
+            continue;
+        }
+        used_old.insert(stored.card_hash.clone());
+        used_new.insert(card.card_hash.clone());
+        edits.push(CardEdit {
+            from: stored.card_hash.clone(),
+            to: card.card_hash.clone(),
+            location: card.location(),
+            similarity,
+        });
+    }
+    edits
+}
+
+/// One minus the edit distance between `a` and `b`, relative to the longer of the two,
+/// ignoring case and runs of whitespace.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let normalize = |s: &str| -> Vec<char> {
+        s.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+            .chars()
+            .collect()
+    };
+    let (a, b) = (normalize(a), normalize(b));
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::parser::content_to_card;
+
+    fn stored(hash: &str, line: i64, content: &str) -> StoredCard {
+        StoredCard {
+            card_hash: hash.into(),
+            file_line: line,
+            content: content.into(),
+        }
+    }
+
+    #[test]
+    fn similarity_ignores_case_and_spacing() {
+        assert_eq!(similarity("Capital of  Japan", "capital of japan"), 1.0);
+        assert!(similarity("Capital of Japan?\nTokyo", "Capital city of Japan?\nTokyo") > 0.8);
+        assert!(similarity("Capital of Japan?\nTokyo", "Boiling point of water?\n100C") < 0.4);
+    }
+
+    #[test]
+    fn matches_reworded_cards_nearby() {
+        let path = Path::new("geo.md");
+        let reworded =
+            content_to_card(path, "Q: Capital city of Japan?\nA: Tokyo\n", 3, 5).unwrap();
+        let unrelated = content_to_card(path, "Q: Largest ocean?\nA: Pacific\n", 6, 8).unwrap();
+        let gone = vec![
+            stored("old-japan", 2, "Capital of Japan?\nTokyo"),
+            stored("old-far", 40, "Capital of Japan?\nTokyo"),
+        ];
+
+        let edits = match_edits(&gone, &[&unrelated, &reworded]);
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].from, "old-japan");
+        assert_eq!(edits[0].to, reworded.card_hash);
+        assert_eq!(edits[0].location, "geo.md:4");
+    }
+}
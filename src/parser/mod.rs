@@ -1,11 +1,17 @@
+pub mod card_id;
+pub mod edits;
 pub mod hash;
 pub mod markdown;
 pub mod media;
 pub mod parse_from_file;
+pub mod tags;
 
+pub use card_id::{format_card_id, new_card_id, strip_card_id};
 pub use hash::get_hash;
 pub use markdown::render_markdown;
 pub use media::{Media, MediaKind, extract_media};
 pub use parse_from_file::{
-    FileSearchStats, cards_from_md, cards_from_text, content_to_card, register_all_cards,
+    FileSearchStats, cards_from_md, cards_from_text, content_to_card, content_to_cards,
+    markdown_files, register_all_cards, sibling_hash,
 };
+pub use tags::TagFilter;
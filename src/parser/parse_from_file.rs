@@ -1,12 +1,16 @@
-use crate::cloze_utils::find_cloze_ranges;
+use crate::cloze_utils::{cloze_card_starts, find_cloze_ranges, parse_deletions};
 use ignore::WalkBuilder;
 use std::path::{Path, PathBuf};
 
 use crate::card::{Card, CardContent, ClozeRange};
-use crate::parser::get_hash;
+use crate::palette::Palette;
+use crate::parser::edits::carry_over_edits;
+use crate::parser::tags::{frontmatter_tags, strip_tags};
+use crate::parser::{get_hash, strip_card_id};
 use crate::utils::{is_markdown, trim_line};
 use ignore::WalkState;
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 
@@ -20,21 +24,37 @@ pub struct FileSearchStats {
     pub markdown_files: usize,
 }
 
-fn parse_card_lines(contents: &str) -> (Option<String>, Option<String>, Option<String>) {
+/// The sections of one card's text.
+struct CardLines {
+    question: Option<String>,
+    answer: Option<String>,
+    cloze: Option<String>,
+    hint: Option<String>,
+    notes: Option<String>,
+    /// Written with `R:` or `:::`, so it is also asked from answer to question.
+    reversible: bool,
+}
+
+fn parse_card_lines(contents: &str) -> CardLines {
     #[derive(Copy, Clone)]
     enum Section {
         Question,
         Answer,
         Cloze,
+        Hint,
+        Notes,
         None,
     }
 
     let mut question_lines: Vec<&str> = Vec::new();
     let mut answer_lines: Vec<&str> = Vec::new();
     let mut cloze_lines: Vec<&str> = Vec::new();
+    let mut hint_lines: Vec<&str> = Vec::new();
+    let mut notes_lines: Vec<&str> = Vec::new();
 
     let mut section = Section::None;
     let mut in_code_block = false;
+    let mut reversible = false;
 
     for raw_line in contents.lines() {
         // Track fenced code blocks so we preserve indentation inside them
@@ -48,6 +68,8 @@ fn parse_card_lines(contents: &str) -> (Option<String>, Option<String>, Option<S
                 Section::Question => question_lines.push(raw_line),
                 Section::Answer => answer_lines.push(raw_line),
                 Section::Cloze => cloze_lines.push(raw_line),
+                Section::Hint => hint_lines.push(raw_line),
+                Section::Notes => notes_lines.push(raw_line),
                 Section::None => {}
             }
             continue;
@@ -60,6 +82,8 @@ fn parse_card_lines(contents: &str) -> (Option<String>, Option<String>, Option<S
                 Section::Question => question_lines.push(""),
                 Section::Answer => answer_lines.push(""),
                 Section::Cloze => cloze_lines.push(""),
+                Section::Hint => hint_lines.push(""),
+                Section::Notes => notes_lines.push(""),
                 Section::None => {}
             }
             continue;
@@ -67,15 +91,16 @@ fn parse_card_lines(contents: &str) -> (Option<String>, Option<String>, Option<S
 
         let line = trimmed.unwrap();
         if line == "---" {
-            return (
-                join_nonempty(question_lines),
-                join_nonempty(answer_lines),
-                join_nonempty(cloze_lines),
-            );
+            break;
         }
 
-        if let Some(rest) = line.strip_prefix("Q:") {
+        let question = line
+            .strip_prefix("Q:")
+            .map(|rest| (rest, false))
+            .or_else(|| line.strip_prefix("R:").map(|rest| (rest, true)));
+        if let Some((rest, reverse)) = question {
             section = Section::Question;
+            reversible = reverse;
             question_lines.clear();
             if let Some(v) = trim_line(rest) {
                 question_lines.push(v);
@@ -101,8 +126,29 @@ fn parse_card_lines(contents: &str) -> (Option<String>, Option<String>, Option<S
             continue;
         }
 
+        if let Some(rest) = line.strip_prefix("H:") {
+            section = Section::Hint;
+            hint_lines.clear();
+            if let Some(v) = trim_line(rest) {
+                hint_lines.push(v);
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("N:") {
+            section = Section::Notes;
+            notes_lines.clear();
+            if let Some(v) = trim_line(rest) {
+                notes_lines.push(v);
+            }
+            continue;
+        }
+
         if matches!(section, Section::None)
-            && let Some((left, right)) = line.split_once("::")
+            && let Some((left, right)) = line
+                .split_once(":::")
+                .inspect(|_| reversible = true)
+                .or_else(|| line.split_once("::"))
         {
             if let Some(left) = trim_line(left)
                 && let Some(right) = trim_line(right)
@@ -117,6 +163,8 @@ fn parse_card_lines(contents: &str) -> (Option<String>, Option<String>, Option<S
             Section::Question => question_lines.push(line),
             Section::Answer => answer_lines.push(line),
             Section::Cloze => cloze_lines.push(line),
+            Section::Hint => hint_lines.push(line),
+            Section::Notes => notes_lines.push(line),
             Section::None => {}
         }
     }
@@ -146,18 +194,62 @@ fn parse_card_lines(contents: &str) -> (Option<String>, Option<String>, Option<S
         }
     }
 
-    (
-        join_nonempty(question_lines),
-        join_nonempty(answer_lines),
-        join_nonempty(cloze_lines),
-    )
+    CardLines {
+        question: join_nonempty(question_lines),
+        answer: join_nonempty(answer_lines),
+        cloze: join_nonempty(cloze_lines),
+        hint: join_nonempty(hint_lines),
+        notes: join_nonempty(notes_lines),
+        reversible,
+    }
+}
+
+/// `contents` without its `H:` and `N:` sections, which are left out of the hash so that
+/// hints and notes can be added to a card without losing its history.
+fn without_extra_sections(contents: &str) -> String {
+    let mut kept = String::with_capacity(contents.len());
+    let mut in_extra = false;
+    let mut in_code_block = false;
+    for line in contents.lines() {
+        let trimmed = line.trim_start();
+        let fence = trimmed.starts_with("```");
+        if !in_code_block && !fence {
+            if trimmed.starts_with("H:") || trimmed.starts_with("N:") {
+                in_extra = true;
+            } else if ["Q:", "A:", "C:", "R:", "---"]
+                .iter()
+                .any(|marker| trimmed.starts_with(marker))
+            {
+                in_extra = false;
+            }
+        }
+        if fence {
+            in_code_block = !in_code_block;
+        }
+        if !in_extra {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+    }
+    kept
 }
+
 pub fn content_to_card(
     card_path: &Path,
     contents: &str,
     file_start_idx: usize,
     file_end_idx: usize,
 ) -> Result<Card> {
+    parse_card(card_path, contents, file_start_idx, file_end_idx).map(|(card, _)| card)
+}
+
+/// The card in `contents`, and whether it is reversible.
+fn parse_card(
+    card_path: &Path,
+    contents: &str,
+    file_start_idx: usize,
+    file_end_idx: usize,
+) -> Result<(Card, bool)> {
     fn card_location(path: &Path, start_idx: usize, end_idx: usize) -> String {
         format!(
             "{} (lines {}-{})",
@@ -182,27 +274,46 @@ pub fn content_to_card(
     }
 
     let location = card_location(card_path, file_start_idx, file_end_idx);
-    let (question, answer, cloze) = parse_card_lines(contents);
-
-    let card_hash = get_hash(contents).ok_or_else(|| {
-        anyhow!(
-            "Unable to hash card from {}.\nContent:\n{}",
-            location,
-            card_content_preview(contents)
-        )
-    })?;
+    let (contents, card_id) = strip_card_id(contents)
+        .with_context(|| format!("Invalid card id in card from {}", location))?;
+    let (contents, tags) = strip_tags(&contents);
+    let contents = contents.as_str();
+    let CardLines {
+        question,
+        answer,
+        cloze,
+        hint,
+        notes,
+        reversible,
+    } = parse_card_lines(contents);
+
+    // a stable id keeps the card's history when its wording changes
+    let card_hash = match card_id {
+        Some(id) => id,
+        None => get_hash(&without_extra_sections(contents)).ok_or_else(|| {
+            anyhow!(
+                "Unable to hash card from {}.\nContent:\n{}",
+                location,
+                card_content_preview(contents)
+            )
+        })?,
+    };
     if let (Some(q), Some(a)) = (question, answer) {
         let content = CardContent::Basic {
             question: q,
             answer: a,
+            hint,
+            notes,
         };
 
-        Ok(Card::new(
+        let mut card = Card::new(
             card_path.to_path_buf(),
             (file_start_idx, file_end_idx),
             content,
             card_hash,
-        ))
+        );
+        card.tags = tags;
+        Ok((card, reversible))
     } else if let Some(c) = cloze {
         let cloze_idxs = find_cloze_ranges(&c);
         let cloze_range: Option<ClozeRange> = cloze_idxs
@@ -216,17 +327,31 @@ pub fn content_to_card(
                     card_content_preview(contents)
                 )
             })?;
+        if parse_deletions(&c)
+            .iter()
+            .any(|deletion| deletion.answer.is_empty())
+        {
+            bail!(
+                "Empty cloze deletion in card from {}.\nContent:\n{}",
+                location,
+                card_content_preview(contents)
+            );
+        }
 
         let content = CardContent::Cloze {
             text: c,
             cloze_range,
+            hint,
+            notes,
         };
-        Ok(Card::new(
+        let mut card = Card::new(
             card_path.to_path_buf(),
             (file_start_idx, file_end_idx),
             content,
             card_hash,
-        ))
+        );
+        card.tags = tags;
+        Ok((card, false))
     } else {
         bail!(
             "Unable to parse card from {}.\nContent:\n{}",
@@ -236,33 +361,112 @@ pub fn content_to_card(
     }
 }
 
+/// The hash of a card's `sibling`th sibling, given the hash `base` of its first. The first
+/// sibling keeps `base`, so a card that gains siblings keeps its history.
+pub fn sibling_hash(base: &str, sibling: usize) -> String {
+    if sibling == 0 {
+        base.to_string()
+    } else {
+        format!("{base}-c{}", sibling + 1)
+    }
+}
+
+/// Like [`content_to_card`], but a cloze card with several deletions becomes one sibling
+/// card per deletion, or per `cN:` group of deletions, and a reversible card becomes a
+/// forward and a reverse sibling, each with its own hash.
+pub fn content_to_cards(
+    card_path: &Path,
+    contents: &str,
+    file_start_idx: usize,
+    file_end_idx: usize,
+) -> Result<Vec<Card>> {
+    let (card, reversible) = parse_card(card_path, contents, file_start_idx, file_end_idx)?;
+    if let (
+        true,
+        CardContent::Basic {
+            question,
+            answer,
+            notes,
+            ..
+        },
+    ) = (reversible, &card.content)
+    {
+        let mut reverse = card.clone();
+        // the hint is written for the question, so it would only give the reverse away
+        reverse.content = CardContent::Basic {
+            question: answer.clone(),
+            answer: question.clone(),
+            hint: None,
+            notes: notes.clone(),
+        };
+        reverse.card_hash = sibling_hash(&card.card_hash, 1);
+        reverse.sibling = 1;
+        return Ok(vec![card, reverse]);
+    }
+    let CardContent::Cloze {
+        text, hint, notes, ..
+    } = &card.content
+    else {
+        return Ok(vec![card]);
+    };
+    let deletions = parse_deletions(text);
+    let starts = cloze_card_starts(&deletions);
+    if starts.len() < 2 {
+        return Ok(vec![card]);
+    }
+    starts
+        .iter()
+        .enumerate()
+        .map(|(sibling, deletion)| {
+            let cloze_range = ClozeRange::new(deletion.start, deletion.end).with_context(|| {
+                format!(
+                    "Invalid cloze range in card from {} (line {})",
+                    card_path.display(),
+                    file_start_idx + 1
+                )
+            })?;
+            let mut card = card.clone();
+            card.content = CardContent::Cloze {
+                text: text.clone(),
+                cloze_range: Some(cloze_range),
+                hint: hint.clone(),
+                notes: notes.clone(),
+            };
+            card.card_hash = sibling_hash(&card.card_hash, sibling);
+            card.sibling = sibling;
+            Ok(card)
+        })
+        .collect()
+}
+
 pub fn cards_from_text(path: &Path, text: &str) -> Result<Vec<Card>> {
+    let (file_tags, frontmatter_lines) = frontmatter_tags(text);
     let mut cards = Vec::new();
     let mut track_buffer = false;
     let mut buffer = String::new();
     let mut start_idx = 0;
     let mut last_idx = 0;
 
-    for (line_idx, line_content) in text.lines().enumerate() {
+    for (line_idx, line_content) in text.lines().enumerate().skip(frontmatter_lines) {
         let line = format!("{}\n", line_content);
 
-        if line.starts_with("Q:") || line.starts_with("C:") {
+        if line.starts_with("Q:") || line.starts_with("R:") || line.starts_with("C:") {
             track_buffer = true;
             if trim_line(&buffer).is_some() {
-                cards.push(content_to_card(path, &buffer, start_idx, line_idx)?);
+                cards.extend(content_to_cards(path, &buffer, start_idx, line_idx)?);
                 buffer.clear();
             }
             start_idx = line_idx;
         }
         if !track_buffer && line.contains("::") {
             if trim_line(&buffer).is_some() {
-                cards.push(content_to_card(path, &buffer, start_idx, line_idx)?);
+                cards.extend(content_to_cards(path, &buffer, start_idx, line_idx)?);
                 buffer.clear();
             }
-            cards.push(content_to_card(path, &line, line_idx, line_idx)?);
+            cards.extend(content_to_cards(path, &line, line_idx, line_idx)?);
         }
         if line.starts_with("---") && trim_line(&buffer).is_some() {
-            cards.push(content_to_card(path, &buffer, start_idx, line_idx)?);
+            cards.extend(content_to_cards(path, &buffer, start_idx, line_idx)?);
             buffer.clear();
             track_buffer = false;
         }
@@ -272,9 +476,16 @@ pub fn cards_from_text(path: &Path, text: &str) -> Result<Vec<Card>> {
         last_idx = line_idx;
     }
     if !buffer.is_empty() {
-        cards.push(content_to_card(path, &buffer, start_idx, last_idx + 1)?);
+        cards.extend(content_to_cards(path, &buffer, start_idx, last_idx + 1)?);
     }
 
+    if !file_tags.is_empty() {
+        for card in &mut cards {
+            card.tags.extend(file_tags.iter().cloned());
+            card.tags.sort();
+            card.tags.dedup();
+        }
+    }
     Ok(cards)
 }
 
@@ -296,9 +507,26 @@ fn markdown_walk_builder(paths: &[PathBuf]) -> Result<Option<WalkBuilder>> {
     Ok(Some(builder))
 }
 
+/// Every markdown file under `paths`, skipping the same files as `register_all_cards`.
+pub fn markdown_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let Some(builder) = markdown_walk_builder(paths)? else {
+        return Ok(Vec::new());
+    };
+    let mut files = Vec::new();
+    for entry in builder.build() {
+        let entry = entry?;
+        if entry.file_type().is_some_and(|ft| ft.is_file()) && is_markdown(entry.path()) {
+            files.push(entry.into_path());
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
 fn run_card_walker(
     paths: Vec<PathBuf>,
-    sender: mpsc::UnboundedSender<Vec<Card>>,
+    sender: mpsc::UnboundedSender<(PathBuf, Vec<Card>)>,
 ) -> Result<FileSearchStats> {
     let Some(builder) = markdown_walk_builder(&paths)? else {
         return Ok(FileSearchStats::default());
@@ -331,11 +559,9 @@ fn run_card_walker(
                 }
                 let path = entry.path().to_path_buf();
                 match cards_from_md(&path) {
+                    // files without cards are sent too, so cards deleted from them are noticed
                     Ok(cards) => {
-                        if cards.is_empty() {
-                            return WalkState::Continue;
-                        }
-                        if sender.send(cards).is_err() {
+                        if sender.send((path, cards)).is_err() {
                             return WalkState::Quit;
                         }
                     }
@@ -370,19 +596,59 @@ pub async fn register_all_cards(
     db: &DB,
     paths: Vec<PathBuf>,
 ) -> Result<(HashMap<String, Card>, FileSearchStats)> {
-    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<Card>>();
+    let (tx, mut rx) = mpsc::unbounded_channel::<(PathBuf, Vec<Card>)>();
     let walker_handle = tokio::task::spawn_blocking(move || run_card_walker(paths, tx));
 
-    let mut hash_cards = HashMap::new();
-    while let Some(batch) = rx.recv().await {
-        if batch.is_empty() {
-            continue;
-        }
-        db.add_cards_batch(&batch).await?;
+    let mut hash_cards: HashMap<String, Card> = HashMap::new();
+    let mut duplicates: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut files = Vec::new();
+    while let Some((path, batch)) = rx.recv().await {
+        files.push(path);
         for card in batch {
-            hash_cards.insert(card.card_hash.clone(), card);
+            match hash_cards.entry(card.card_hash.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(card);
+                }
+                // a card copied along with its id; keep the earliest so the choice is stable
+                Entry::Occupied(mut entry) => {
+                    duplicates
+                        .entry(card.card_hash.clone())
+                        .or_insert_with(|| vec![entry.get().location()])
+                        .push(card.location());
+                    let kept = entry.get();
+                    if (&card.file_path, card.file_card_range)
+                        < (&kept.file_path, kept.file_card_range)
+                    {
+                        entry.insert(card);
+                    }
+                }
+            }
         }
     }
+    for (hash, mut places) in duplicates {
+        places.sort();
+        println!(
+            "{} {} {}",
+            Palette::paint(Palette::WARNING, "Duplicate card"),
+            Palette::paint(Palette::DANGER, &hash),
+            Palette::dim(format!(
+                "at {}; only {} is drilled",
+                places.join(", "),
+                hash_cards[&hash].location()
+            ))
+        );
+    }
+
+    for edit in carry_over_edits(db, &files, &hash_cards).await? {
+        println!(
+            "{} {} {}",
+            Palette::paint(Palette::INFO, "Kept progress for edited card"),
+            Palette::paint(Palette::SUCCESS, &edit.location),
+            Palette::dim(format!("({:.0}% similar)", edit.similarity * 100.0))
+        );
+    }
+    let cards: Vec<Card> = hash_cards.values().cloned().collect();
+    db.add_cards_batch(&cards).await?;
 
     let stats = walker_handle.await??;
 
@@ -391,7 +657,10 @@ pub async fn register_all_cards(
 
 #[cfg(test)]
 mod tests {
-    use super::{cards_from_md, content_to_card, parse_card_lines, register_all_cards};
+    use super::{
+        cards_from_md, cards_from_text, content_to_card, content_to_cards, parse_card_lines,
+        register_all_cards,
+    };
     use crate::card::CardContent;
     use crate::crud::DB;
     use std::path::PathBuf;
@@ -399,9 +668,12 @@ mod tests {
     #[test]
     fn test_card_parsing() {
         let contents = "C:\nRegion: [`us-east-2`]\n\nLocation: [Ohio]\n\n---\n\n";
-        let (question, _, cloze) = parse_card_lines(contents);
-        assert!(question.is_none());
-        assert_eq!("Region: [`us-east-2`]\n\nLocation: [Ohio]", cloze.unwrap());
+        let lines = parse_card_lines(contents);
+        assert!(lines.question.is_none());
+        assert_eq!(
+            "Region: [`us-east-2`]\n\nLocation: [Ohio]",
+            lines.cloze.unwrap()
+        );
     }
 
     #[test]
@@ -416,7 +688,10 @@ mod tests {
 
         let content = "Q: what?\nA: yes\n\n";
         let card = content_to_card(&card_path, content, 1, 1).unwrap();
-        if let CardContent::Basic { question, answer } = &card.content {
+        if let CardContent::Basic {
+            question, answer, ..
+        } = &card.content
+        {
             assert_eq!(question, "what?");
             assert_eq!(answer, "yes");
         } else {
@@ -434,7 +709,10 @@ mod tests {
 
         let content = "C: ping? [pong]";
         let card = content_to_card(&card_path, content, 1, 1);
-        if let CardContent::Cloze { text, cloze_range } = &card.expect("should be basic").content {
+        if let CardContent::Cloze {
+            text, cloze_range, ..
+        } = &card.expect("should be basic").content
+        {
             assert_eq!(text, "ping? [pong]");
             let range = cloze_range.as_ref().expect("range to exist");
             assert_eq!(range.start, 6_usize);
@@ -490,7 +768,10 @@ mod tests {
         let content = "C: this has no cloze markers";
         let card = content_to_card(&card_path, content, 0, 1)
             .expect("invalid cloze text should still be accepted");
-        if let CardContent::Cloze { text, cloze_range } = card.content {
+        if let CardContent::Cloze {
+            text, cloze_range, ..
+        } = card.content
+        {
             assert_eq!(text, "this has no cloze markers");
             assert!(cloze_range.is_none());
         } else {
@@ -559,7 +840,10 @@ mod tests {
             "expected exactly one card, got {}",
             cards.len()
         );
-        if let CardContent::Basic { question, answer } = &cards[0].content {
+        if let CardContent::Basic {
+            question, answer, ..
+        } = &cards[0].content
+        {
             assert_eq!(question, "some rust code?");
             assert!(answer.contains("#[tokio::main]"));
             assert!(answer.contains("std::process::exit(1);"));
@@ -572,11 +856,162 @@ mod tests {
         }
     }
 
+    #[test]
+    fn card_id_replaces_the_content_hash() {
+        let card_path = PathBuf::from("test.md");
+        let card =
+            content_to_card(&card_path, "Q: what?\nA: yes\n<!-- id: 7f3a -->\n", 0, 2).unwrap();
+        assert_eq!(card.card_hash, "7f3a");
+        if let CardContent::Basic { answer, .. } = &card.content {
+            assert_eq!(answer, "yes");
+        } else {
+            panic!("Expected CardContent::Basic");
+        }
+
+        let reworded = content_to_card(
+            &card_path,
+            "Q: what now?\nA: yes\n<!-- id: 7f3a -->\n",
+            0,
+            2,
+        )
+        .unwrap();
+        assert_eq!(reworded.card_hash, card.card_hash);
+
+        let card = content_to_card(&card_path, "what::remnote <!-- id: r1 -->\n", 0, 0).unwrap();
+        assert_eq!(card.card_hash, "r1");
+        let plain = content_to_card(&card_path, "what::remnote\n", 0, 0).unwrap();
+        assert_ne!(plain.card_hash, "r1");
+    }
+
+    #[test]
+    fn multi_cloze_cards_become_siblings() {
+        let card_path = PathBuf::from("test.md");
+        let content = "C: The [order] of a group is [the cardinality of its underlying set].";
+        let single = content_to_card(&card_path, content, 0, 1).unwrap();
+        let cards = content_to_cards(&card_path, content, 0, 1).unwrap();
+        assert_eq!(cards.len(), 2);
+        // the first deletion keeps the hash the card had before it had siblings
+        assert_eq!(cards[0].card_hash, single.card_hash);
+        assert_ne!(cards[1].card_hash, cards[0].card_hash);
+        assert!(cards[0].is_sibling_of(&cards[1]));
+        assert_eq!(cards[1].sibling, 1);
+        let CardContent::Cloze {
+            cloze_range: Some(range),
+            text,
+            ..
+        } = &cards[1].content
+        else {
+            panic!("Expected CardContent::Cloze");
+        };
+        assert_eq!(
+            &text[range.start..range.end],
+            "[the cardinality of its underlying set]"
+        );
+
+        let cards = content_to_cards(&card_path, "C: [ping] [pong] <!-- id: pp -->", 0, 1).unwrap();
+        let hashes: Vec<&str> = cards.iter().map(|card| card.card_hash.as_str()).collect();
+        assert_eq!(hashes, vec!["pp", "pp-c2"]);
+    }
+
+    #[test]
+    fn cloze_groups_share_a_card() {
+        let card_path = PathBuf::from("test.md");
+        let content = "C: [c1: Tokyo] and [c1: Osaka] are cities; [c2: Fuji] is a mountain";
+        let cards = content_to_cards(&card_path, content, 0, 1).unwrap();
+        assert_eq!(cards.len(), 2);
+        assert!(content_to_card(&card_path, "C: [c1: ] is empty", 0, 1).is_err());
+        assert!(content_to_card(&card_path, "C: [fine] and []", 0, 1).is_err());
+    }
+
+    #[test]
+    fn reversible_cards_go_both_ways() {
+        let card_path = PathBuf::from("test.md");
+        for content in ["R: hund\nA: dog\n", "hund:::dog\n"] {
+            let cards = content_to_cards(&card_path, content, 0, 1).unwrap();
+            assert_eq!(cards.len(), 2, "{content}");
+            let sides: Vec<(&str, &str)> = cards
+                .iter()
+                .map(|card| match &card.content {
+                    CardContent::Basic {
+                        question, answer, ..
+                    } => (question.as_str(), answer.as_str()),
+                    _ => panic!("Expected CardContent::Basic"),
+                })
+                .collect();
+            assert_eq!(sides, vec![("hund", "dog"), ("dog", "hund")]);
+            assert!(cards[0].is_sibling_of(&cards[1]));
+        }
+
+        let cards = content_to_cards(&card_path, "Q: hund\nA: dog\n", 0, 1).unwrap();
+        assert_eq!(cards.len(), 1);
+    }
+
+    #[test]
+    fn hints_and_notes_leave_the_hash_alone() {
+        let card_path = PathBuf::from("test.md");
+        let plain = content_to_card(&card_path, "Q: Capital of Japan?\nA: Tokyo\n", 0, 1).unwrap();
+        let content = "Q: Capital of Japan?\nH: Starts with T\nA: Tokyo\nN: Capital since 1868.\n```\nA: not a marker\n```\n";
+        let card = content_to_card(&card_path, content, 0, 1).unwrap();
+        assert_eq!(card.card_hash, plain.card_hash);
+        assert_eq!(card.content.hint(), Some("Starts with T"));
+        assert_eq!(
+            card.content.notes(),
+            Some("Capital since 1868.\n```\nA: not a marker\n```")
+        );
+        assert_eq!(card.text(), plain.text());
+
+        let cloze = content_to_card(&card_path, "C: [Tokyo] is big\nN: Very big\n", 0, 1).unwrap();
+        let plain = content_to_card(&card_path, "C: [Tokyo] is big\n", 0, 1).unwrap();
+        assert_eq!(cloze.card_hash, plain.card_hash);
+        assert_eq!(cloze.content.notes(), Some("Very big"));
+
+        let cards =
+            content_to_cards(&card_path, "R: hund\nH: animal\nA: dog\nN: noun\n", 0, 1).unwrap();
+        assert_eq!(cards[1].content.hint(), None);
+        assert_eq!(cards[1].content.notes(), Some("noun"));
+    }
+
+    #[test]
+    fn tags_come_from_cards_and_frontmatter() {
+        let card_path = PathBuf::from("test.md");
+        let text = "---\ntags: [interview]\n---\nQ: What is a lifetime? #rust\nA: A scope\ntags: memory\n---\nOhm's law::V = IR\n";
+        let cards = cards_from_text(&card_path, text).unwrap();
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].tags, vec!["interview", "memory", "rust"]);
+        assert_eq!(cards[0].file_card_range.0, 3);
+        assert_eq!(cards[1].tags, vec!["interview"]);
+
+        let plain =
+            content_to_card(&card_path, "Q: What is a lifetime?\nA: A scope\n", 0, 1).unwrap();
+        assert_eq!(cards[0].card_hash, plain.card_hash);
+        assert_eq!(cards[0].text(), plain.text());
+    }
+
+    #[test]
+    fn hashes_in_text_keep_the_pre_tag_hash() {
+        let card_path = PathBuf::from("test.md");
+        for content in [
+            "Q: What does #include do?\nA: Pastes in a header\n",
+            "Q: When is #pragma once used?\nA: In headers, to avoid double inclusion\n",
+        ] {
+            let card = content_to_card(&card_path, content, 0, 1).unwrap();
+            assert_eq!(
+                Some(card.card_hash.clone()),
+                crate::parser::get_hash(content)
+            );
+            assert!(card.text().contains('#'));
+            assert!(card.tags.is_empty());
+        }
+    }
+
     #[test]
     fn test_single_line_remnote() {
         let card_path = PathBuf::from("test.md");
         let card = content_to_card(&card_path, "what is this::remnote  \n", 0, 1).unwrap();
-        if let CardContent::Basic { question, answer } = &card.content {
+        if let CardContent::Basic {
+            question, answer, ..
+        } = &card.content
+        {
             assert_eq!(question, "what is this");
             assert_eq!(answer, "remnote");
         } else {
@@ -587,6 +1022,110 @@ mod tests {
         assert!(card.is_err());
     }
 
+    #[tokio::test]
+    async fn reworded_cards_keep_their_progress() {
+        use crate::crud::cards::ReviewOptions;
+        use crate::fsrs::{Performance, ReviewStatus};
+        use std::fs;
+
+        let db = DB::new_in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let deck = dir.path().join("deck.md");
+        fs::write(
+            &deck,
+            "Q: Capital of Japan?\nA: Tokyo\n\nQ: Largest ocean?\nA: Pacific\n",
+        )
+        .unwrap();
+        let paths = vec![dir.path().to_path_buf()];
+        let (cards, _) = register_all_cards(&db, paths.clone()).await.unwrap();
+        for card in cards.values() {
+            db.update_card_performance(
+                card,
+                ReviewStatus::Good,
+                None,
+                &ReviewOptions::default(),
+                std::time::Duration::ZERO,
+            )
+            .await
+            .unwrap();
+        }
+
+        fs::write(
+            &deck,
+            "Q: What is the capital of Japan?\nA: Tokyo\n\nQ: Boiling point of water?\nA: 100C\n",
+        )
+        .unwrap();
+        let (cards, _) = register_all_cards(&db, paths).await.unwrap();
+        for card in cards.values() {
+            let reviewed = matches!(
+                db.get_card_performance(card).await.unwrap(),
+                Performance::Reviewed(_)
+            );
+            // the reworded card kept its history; the replaced one starts fresh
+            assert_eq!(reviewed, card.text().contains("Japan"));
+        }
+    }
+
+    #[tokio::test]
+    async fn long_deleted_cards_dont_pass_on_their_progress() {
+        use crate::crud::cards::ReviewOptions;
+        use crate::fsrs::{Performance, ReviewStatus};
+        use std::fs;
+
+        let db = DB::new_in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let deck = dir.path().join("deck.md");
+        fs::write(&deck, "Q: Capital of Japan?\nA: Tokyo\n").unwrap();
+        let paths = vec![dir.path().to_path_buf()];
+        let (cards, _) = register_all_cards(&db, paths.clone()).await.unwrap();
+        for card in cards.values() {
+            db.update_card_performance(
+                card,
+                ReviewStatus::Good,
+                None,
+                &ReviewOptions::default(),
+                std::time::Duration::ZERO,
+            )
+            .await
+            .unwrap();
+        }
+
+        // the card is deleted, and a similar one is written there in a later pass
+        fs::write(&deck, "Notes only for now.\n").unwrap();
+        register_all_cards(&db, paths.clone()).await.unwrap();
+        fs::write(&deck, "Q: Capital city of Japan?\nA: Tokyo\n").unwrap();
+        let (cards, _) = register_all_cards(&db, paths).await.unwrap();
+        let card = cards.values().next().unwrap();
+        assert_eq!(
+            db.get_card_performance(card).await.unwrap(),
+            Performance::New
+        );
+    }
+
+    #[tokio::test]
+    async fn copied_ids_keep_the_first_card() {
+        use std::fs;
+
+        let db = DB::new_in_memory().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("a.md"),
+            "Q: Capital of Japan?\nA: Tokyo <!-- id: jp -->\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("b.md"),
+            "Q: Capital of France?\nA: Paris <!-- id: jp -->\n",
+        )
+        .unwrap();
+
+        let (cards, _) = register_all_cards(&db, vec![dir.path().to_path_buf()])
+            .await
+            .unwrap();
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards["jp"].file_path, dir.path().join("a.md"));
+    }
+
     #[tokio::test]
     async fn register_all_cards_returns_error_for_invalid_card_file() {
         use std::fs;
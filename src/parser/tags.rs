@@ -0,0 +1,294 @@
+use anyhow::{Result, anyhow};
+
+use crate::card::Card;
+
+// Tags come from `#tag` tokens at the end of a card's lines, a `tags:` line in a card, or a `tags` key in
+// the file's YAML frontmatter, which applies to every card in the file.
+
+const TAGS_KEY: &str = "tags:";
+const FRONTMATTER_FENCE: &str = "---";
+const CARD_MARKERS: [&str; 6] = ["Q:", "A:", "C:", "R:", "H:", "N:"];
+
+/// Which tags a card needs to be drilled: at least one of `include`, if any are given,
+/// and none of `exclude`. A tag also matches the tags nested under it, so `interview`
+/// matches `interview/rust`.
+#[derive(Clone, Debug, Default)]
+pub struct TagFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl TagFilter {
+    /// Fails on a tag no card could have, rather than ignoring it and matching every card.
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        let normalize = |tags: &[String]| -> Result<Vec<String>> {
+            tags.iter()
+                .map(|tag| {
+                    normalize_tag(tag).ok_or_else(|| {
+                        anyhow!(
+                            "'{tag}' is not a valid tag: tags start with a letter and contain only letters, digits, '-', '_', or '/'"
+                        )
+                    })
+                })
+                .collect()
+        };
+        Ok(TagFilter {
+            include: normalize(include)?,
+            exclude: normalize(exclude)?,
+        })
+    }
+
+    pub fn matches(&self, card: &Card) -> bool {
+        let has = |wanted: &String| card.tags.iter().any(|tag| is_within(tag, wanted));
+        (self.include.is_empty() || self.include.iter().any(has)) && !self.exclude.iter().any(has)
+    }
+}
+
+fn is_within(tag: &str, parent: &str) -> bool {
+    tag.strip_prefix(parent)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Split the tags out of a card, returning the rest of its text and its tags, sorted.
+/// Outside of fenced code, `#tag` tokens at the end of a line are dropped, unless that would
+/// leave a section with no text, as in "A: #include". A `#` earlier in a line, as in
+/// "What does #include do?", is left alone. A `tags:` line is only read as tags before the
+/// card starts or as its last line, so answer text starting with "tags:" is kept.
+pub fn strip_tags(contents: &str) -> (String, Vec<String>) {
+    let mut tags = Vec::new();
+    let mut rest = String::with_capacity(contents.len());
+    let last_line = contents
+        .split_inclusive('\n')
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, _)| idx)
+        .last();
+    let mut in_code_block = false;
+    let mut in_card = false;
+    let mut section_has_text = false;
+    for (idx, line) in contents.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block {
+            section_has_text |= in_card;
+            rest.push_str(line);
+            continue;
+        }
+        if (!in_card || Some(idx) == last_line)
+            && let Some(values) = trimmed.strip_prefix(TAGS_KEY)
+        {
+            tags.extend(parse_tag_list(values));
+            continue;
+        }
+        let text = line.trim_end_matches(['\r', '\n']);
+        let starts_section = CARD_MARKERS
+            .iter()
+            .any(|marker| trimmed.starts_with(marker))
+            || text.contains("::");
+        if starts_section {
+            in_card = true;
+            section_has_text = false;
+        }
+        let keep_text = in_card && !section_has_text;
+        let end = trailing_tags(text, keep_text, &mut tags);
+        section_has_text |= in_card && !is_blank(&text[..end]);
+        rest.push_str(&text[..end]);
+        rest.push_str(&line[text.len()..]);
+    }
+
+    tags.sort();
+    tags.dedup();
+    (rest, tags)
+}
+
+/// Collect the `#tag` tokens at the end of `line` into `tags`, returning where the text
+/// before them ends. With `keep_text`, a token is left in place if taking it would leave
+/// the line without text.
+fn trailing_tags(line: &str, keep_text: bool, tags: &mut Vec<String>) -> usize {
+    let mut end = line.len();
+    loop {
+        let head = line[..end].trim_end();
+        let start = head
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let Some(tag) = head[start..].strip_prefix('#').and_then(normalize_tag) else {
+            return end;
+        };
+        let before = head[..start].trim_end();
+        if keep_text && is_blank(before) {
+            return end;
+        }
+        tags.push(tag);
+        end = before.len();
+    }
+}
+
+/// Whether `line` has no card text: it is empty, or only a section marker or `::`.
+fn is_blank(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty() || CARD_MARKERS.contains(&line) || line.ends_with("::")
+}
+
+/// The tags listed under `tags` in the YAML frontmatter at the top of `text`, and the
+/// number of lines the frontmatter takes up. Accepts `tags: a, b`, `tags: [a, b]`, and a
+/// `- a` list on the following lines.
+pub fn frontmatter_tags(text: &str) -> (Vec<String>, usize) {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim_end) != Some(FRONTMATTER_FENCE) {
+        return (Vec::new(), 0);
+    }
+    let mut tags = Vec::new();
+    let mut in_tags = false;
+    for (idx, line) in lines.enumerate() {
+        let trimmed = line.trim();
+        if trimmed == FRONTMATTER_FENCE || trimmed == "..." {
+            tags.sort();
+            tags.dedup();
+            return (tags, idx + 2);
+        }
+        // a deck that opens with a `---` rule, not frontmatter
+        if CARD_MARKERS.iter().any(|marker| line.starts_with(marker)) || line.contains("::") {
+            return (Vec::new(), 0);
+        }
+        if let Some(values) = line.strip_prefix(TAGS_KEY) {
+            in_tags = true;
+            tags.extend(parse_tag_list(values));
+        } else if in_tags && let Some(item) = trimmed.strip_prefix('-') {
+            tags.extend(parse_tag_list(item));
+        } else if !line.starts_with(char::is_whitespace) {
+            in_tags = false;
+        }
+    }
+    // no closing fence, so it wasn't frontmatter after all
+    (Vec::new(), 0)
+}
+
+/// Tags separated by commas or whitespace, optionally bracketed, quoted, or written as `#tag`.
+fn parse_tag_list(values: &str) -> Vec<String> {
+    values
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(|value| normalize_tag(value.trim_matches(['[', ']', '"', '\''])))
+        .collect()
+}
+
+/// `tag` in lowercase without its `#`, if it is a valid tag: a letter followed by letters,
+/// digits, `-`, `_`, or `/`.
+fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag
+        .trim()
+        .trim_start_matches('#')
+        .trim_end_matches(['-', '/']);
+    let first = tag.chars().next()?;
+    (first.is_alphabetic() && tag.chars().all(is_tag_char)).then(|| tag.to_lowercase())
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_trailing_tags_and_tag_lines() {
+        let (rest, tags) = strip_tags(
+            "Q: What is a lifetime? #Rust #interview\r\nA: A scope #interview/rust\ntags: systems, #memory\n",
+        );
+        assert_eq!(rest, "Q: What is a lifetime?\r\nA: A scope\n");
+        assert_eq!(
+            tags,
+            vec!["interview", "interview/rust", "memory", "rust", "systems"]
+        );
+
+        let text =
+            "Q: What does #include do in C#?\nA: `#[derive]`, issue #12, [docs](https://a.io/#x)\n";
+        assert_eq!(strip_tags(text), (text.to_string(), Vec::new()));
+
+        let fenced = "Q: Comment?\nA:\n```python\nx = 1 #comment\n```\n";
+        assert_eq!(strip_tags(fenced), (fenced.to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn keeps_a_tag_shaped_answer() {
+        let text = "Q: How do you include a header in C?\nA: #include";
+        assert_eq!(strip_tags(text), (text.to_string(), Vec::new()));
+
+        let (rest, tags) = strip_tags("Q: Macro?\nA: #define #c\n");
+        assert_eq!(rest, "Q: Macro?\nA: #define\n");
+        assert_eq!(tags, vec!["c"]);
+
+        let text = "Q: Pragma?\nA:\n#pragma\n";
+        assert_eq!(strip_tags(text), (text.to_string(), Vec::new()));
+        assert_eq!(
+            strip_tags("what::#include #c\n"),
+            ("what::#include\n".to_string(), vec!["c".to_string()])
+        );
+    }
+
+    #[test]
+    fn keeps_tags_lines_inside_an_answer() {
+        let text = "Q: What are git tags?\nA: Named commits.\ntags: are how git names releases\nThey don't move.\n";
+        assert_eq!(strip_tags(text), (text.to_string(), Vec::new()));
+
+        let (rest, tags) = strip_tags("tags: git\nQ: What are git tags?\nA: Named commits.\n");
+        assert_eq!(rest, "Q: What are git tags?\nA: Named commits.\n");
+        assert_eq!(tags, vec!["git"]);
+    }
+
+    #[test]
+    fn filters_by_tag_and_nested_tags() {
+        let mut card = Card::new(
+            "deck.md".into(),
+            (0, 1),
+            crate::card::CardContent::Basic {
+                question: "Q".into(),
+                answer: "A".into(),
+                hint: None,
+                notes: None,
+            },
+            "hash".into(),
+        );
+        card.tags = vec!["interview/rust".into(), "memory".into()];
+        let filter = |include: &[&str], exclude: &[&str]| {
+            let owned = |tags: &[&str]| tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+            TagFilter::new(&owned(include), &owned(exclude))
+                .unwrap()
+                .matches(&card)
+        };
+        assert!(filter(&[], &[]));
+        assert!(filter(&["#Interview"], &[]));
+        assert!(filter(&["systems", "memory"], &[]));
+        assert!(!filter(&["inter"], &[]));
+        assert!(!filter(&["interview"], &["memory"]));
+        assert!(!filter(&[], &["interview/rust"]));
+
+        for invalid in ["2024", "1on1", "c++", ""] {
+            assert!(TagFilter::new(&[invalid.to_string()], &[]).is_err());
+            assert!(TagFilter::new(&[], &[invalid.to_string()]).is_err());
+        }
+    }
+
+    #[test]
+    fn reads_frontmatter_tags() {
+        let text = "---\ntitle: Notes\ntags: [interview, Rust]\n---\nQ: a\nA: b\n";
+        assert_eq!(
+            frontmatter_tags(text),
+            (vec!["interview".into(), "rust".into()], 4)
+        );
+
+        let text = "---\ntags:\n  - interview\n  - \"system-design\"\nauthor: me\n---\n";
+        assert_eq!(
+            frontmatter_tags(text).0,
+            vec!["interview".to_string(), "system-design".to_string()]
+        );
+
+        assert_eq!(frontmatter_tags("Q: a\nA: b\n---\n"), (Vec::new(), 0));
+        assert_eq!(frontmatter_tags("---\nQ: a\nA: b\n"), (Vec::new(), 0));
+        assert_eq!(frontmatter_tags("---\nQ: a\nA: b\n---\n"), (Vec::new(), 0));
+    }
+}
@@ -0,0 +1,369 @@
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use fsrs::{SimulatorConfig, simulate};
+
+use crate::crud::revlog::ReviewLogEntry;
+use crate::crud::stats::CardStatsRow;
+use crate::fsrs::{IntervalLimits, LearningSteps, ReviewStatus};
+
+/// Retention targets compared by the simulator, spanning the range `drill --retention` accepts.
+pub const RETENTION_CANDIDATES: [f32; 7] = [0.70, 0.75, 0.80, 0.85, 0.90, 0.95, 0.97];
+
+/// Reviews slower than this are treated as the user walking away and ignored for timing.
+const MAX_REVIEW_SECS: f32 = 20.0 * 60.0;
+const SIMULATION_SEED: u64 = 42;
+
+#[derive(Clone, Debug)]
+pub struct SimulationOptions {
+    pub days: usize,
+    pub new_cards_per_day: usize,
+    pub minutes_per_day: Option<f64>,
+    pub steps: LearningSteps,
+    pub intervals: IntervalLimits,
+}
+
+impl Default for SimulationOptions {
+    fn default() -> Self {
+        Self {
+            days: 365,
+            new_cards_per_day: 20,
+            minutes_per_day: None,
+            steps: LearningSteps::default(),
+            intervals: IntervalLimits::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetentionOutcome {
+    pub retention: f32,
+    pub minutes_per_day: f64,
+    pub reviews_per_day: f64,
+    /// Expected number of cards you can recall at the end of the simulation.
+    pub memorized: f64,
+}
+
+impl RetentionOutcome {
+    /// Cards remembered per minute of total study time.
+    pub fn efficiency(&self, days: usize) -> f64 {
+        let total_minutes = self.minutes_per_day * days as f64;
+        if total_minutes <= 0.0 {
+            0.0
+        } else {
+            self.memorized / total_minutes
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WorkloadSimulation {
+    pub days: usize,
+    pub num_cards: usize,
+    pub outcomes: Vec<RetentionOutcome>,
+    pub recommended: usize,
+    /// False when a daily time budget was given but no target fits in it.
+    pub within_budget: bool,
+}
+
+impl WorkloadSimulation {
+    pub fn recommended_outcome(&self) -> &RetentionOutcome {
+        &self.outcomes[self.recommended]
+    }
+}
+
+/// Simulate the collection forward for each candidate retention target and
+/// pick the one that balances daily workload against expected knowledge.
+pub fn simulate_workload(
+    rows: &[CardStatsRow],
+    review_log: &[ReviewLogEntry],
+    parameters: &[f32],
+    options: &SimulationOptions,
+    now: DateTime<Utc>,
+) -> Result<WorkloadSimulation> {
+    let rows: Vec<&CardStatsRow> = rows.iter().filter(|row| !row.suspended).collect();
+    if rows.is_empty() {
+        bail!("No cards to simulate");
+    }
+    if options.days == 0 {
+        bail!("The simulation needs to cover at least one day");
+    }
+
+    let existing_cards = existing_simulator_cards(&rows, now);
+    let mut outcomes = Vec::with_capacity(RETENTION_CANDIDATES.len());
+    for retention in RETENTION_CANDIDATES {
+        let config = simulator_config(rows.len(), review_log, options);
+        let result = simulate(
+            &config,
+            parameters,
+            retention,
+            Some(SIMULATION_SEED),
+            Some(existing_cards.clone()),
+        )?;
+        let days = options.days as f64;
+        outcomes.push(RetentionOutcome {
+            retention,
+            minutes_per_day: result.cost_per_day.iter().map(|c| *c as f64).sum::<f64>()
+                / 60.0
+                / days,
+            reviews_per_day: (result.review_cnt_per_day.iter().sum::<usize>()
+                + result.learn_cnt_per_day.iter().sum::<usize>())
+                as f64
+                / days,
+            memorized: result
+                .memorized_cnt_per_day
+                .last()
+                .copied()
+                .unwrap_or_default() as f64,
+        });
+    }
+
+    let (recommended, within_budget) = recommend(&outcomes, options);
+    Ok(WorkloadSimulation {
+        days: options.days,
+        num_cards: rows.len(),
+        outcomes,
+        recommended,
+        within_budget,
+    })
+}
+
+/// With a time budget, pick the target that remembers the most while fitting
+/// in it. Without one, pick the target with the most knowledge per minute.
+fn recommend(outcomes: &[RetentionOutcome], options: &SimulationOptions) -> (usize, bool) {
+    if let Some(budget) = options.minutes_per_day {
+        let within_budget = outcomes
+            .iter()
+            .enumerate()
+            .filter(|(_, outcome)| outcome.minutes_per_day <= budget)
+            .max_by(|(_, a), (_, b)| a.memorized.total_cmp(&b.memorized));
+        if let Some((idx, _)) = within_budget {
+            return (idx, true);
+        }
+        let cheapest = outcomes
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.minutes_per_day.total_cmp(&b.minutes_per_day))
+            .map(|(idx, _)| idx)
+            .unwrap_or_default();
+        return (cheapest, false);
+    }
+
+    let best = outcomes
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| {
+            a.efficiency(options.days)
+                .total_cmp(&b.efficiency(options.days))
+        })
+        .map(|(idx, _)| idx)
+        .unwrap_or_default();
+    (best, true)
+}
+
+fn existing_simulator_cards(rows: &[&CardStatsRow], now: DateTime<Utc>) -> Vec<fsrs::Card> {
+    rows.iter()
+        .enumerate()
+        .filter_map(|(idx, row)| {
+            let (Some(stability), Some(difficulty), Some(last_reviewed_at)) =
+                (row.stability, row.difficulty, row.last_reviewed_at)
+            else {
+                return None;
+            };
+            if row.review_count == 0 {
+                return None;
+            }
+            let days_since_review = days_between(last_reviewed_at, now).max(0.0);
+            let days_until_due = row
+                .due_date
+                .map(|due| days_between(now, due).max(0.0))
+                .unwrap_or(0.0);
+            Some(fsrs::Card {
+                id: idx as i64 + 1,
+                difficulty: difficulty as f32,
+                stability: stability as f32,
+                last_date: -days_since_review as f32,
+                due: days_until_due as f32,
+                interval: row.interval_raw.unwrap_or_default() as f32,
+                lapses: 0,
+            })
+        })
+        .collect()
+}
+
+fn days_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    to.signed_duration_since(from).num_seconds() as f64 / 86_400.0
+}
+
+/// Start from the FSRS defaults and replace the answer-time and grade
+/// distributions with the user's own when the review log has them.
+fn simulator_config(
+    deck_size: usize,
+    review_log: &[ReviewLogEntry],
+    options: &SimulationOptions,
+) -> SimulatorConfig {
+    let mut config = SimulatorConfig {
+        deck_size,
+        learn_span: options.days,
+        max_cost_perday: f32::MAX,
+        max_ivl: options.intervals.maximum as f32,
+        learn_limit: options.new_cards_per_day,
+        learning_step_count: options.steps.learning.len(),
+        relearning_step_count: options.steps.relearning.len(),
+        ..Default::default()
+    };
+
+    let mut first_ratings = [0usize; 4];
+    let mut review_ratings = [0usize; 3];
+    let mut seconds = [0f32; 4];
+    let mut timed = [0usize; 4];
+    for entry in review_log {
+        let grade = entry.grade.rating() as usize - 1;
+        if entry.previous_stability.is_none() {
+            first_ratings[grade] += 1;
+        } else if entry.grade != ReviewStatus::Again && entry.elapsed_days >= 1.0 {
+            review_ratings[grade - 1] += 1;
+        }
+        let secs = entry.time_spent.as_secs_f32();
+        if secs > 0.0 && secs < MAX_REVIEW_SECS {
+            seconds[grade] += secs;
+            timed[grade] += 1;
+        }
+    }
+
+    if first_ratings.iter().sum::<usize>() > 0 {
+        config.first_rating_prob = proportions(first_ratings);
+    }
+    if review_ratings.iter().sum::<usize>() > 0 {
+        config.review_rating_prob = proportions(review_ratings);
+    }
+    for (grade, (total, count)) in seconds.iter().zip(timed).enumerate() {
+        if count == 0 {
+            continue;
+        }
+        let mean = total / count as f32;
+        for state_costs in config.state_rating_costs.iter_mut() {
+            state_costs[grade] = mean;
+        }
+    }
+
+    config
+}
+
+fn proportions<const N: usize>(counts: [usize; N]) -> [f32; N] {
+    let total = counts.iter().sum::<usize>().max(1) as f32;
+    counts.map(|count| count as f32 / total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use fsrs::DEFAULT_PARAMETERS;
+
+    fn new_row(hash: &str) -> CardStatsRow {
+        CardStatsRow {
+            card_hash: hash.into(),
+            review_count: 0,
+            due_date: None,
+            interval_raw: None,
+            difficulty: None,
+            stability: None,
+            last_reviewed_at: None,
+            suspended: false,
+            buried_until: None,
+            phase: "learning".into(),
+        }
+    }
+
+    fn reviewed_row(hash: &str, now: DateTime<Utc>) -> CardStatsRow {
+        CardStatsRow {
+            card_hash: hash.into(),
+            review_count: 4,
+            due_date: Some(now + Duration::days(3)),
+            interval_raw: Some(10.0),
+            difficulty: Some(5.0),
+            stability: Some(10.0),
+            last_reviewed_at: Some(now - Duration::days(7)),
+            suspended: false,
+            buried_until: None,
+            phase: "review".into(),
+        }
+    }
+
+    fn collection(now: DateTime<Utc>) -> Vec<CardStatsRow> {
+        (0..40)
+            .map(|i| {
+                if i % 2 == 0 {
+                    new_row(&format!("new-{i}"))
+                } else {
+                    reviewed_row(&format!("reviewed-{i}"), now)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn higher_retention_costs_more_time() {
+        let now = Utc::now();
+        let options = SimulationOptions {
+            days: 90,
+            ..Default::default()
+        };
+        let simulation =
+            simulate_workload(&collection(now), &[], &DEFAULT_PARAMETERS, &options, now).unwrap();
+
+        assert_eq!(simulation.outcomes.len(), RETENTION_CANDIDATES.len());
+        assert_eq!(simulation.num_cards, 40);
+        let first = simulation.outcomes.first().unwrap();
+        let last = simulation.outcomes.last().unwrap();
+        assert!(first.minutes_per_day < last.minutes_per_day);
+        assert!(first.memorized < last.memorized);
+        assert!(simulation.within_budget);
+    }
+
+    #[test]
+    fn budget_picks_best_target_that_fits() {
+        let outcome = |retention: f32, minutes_per_day: f64, memorized: f64| RetentionOutcome {
+            retention,
+            minutes_per_day,
+            reviews_per_day: 0.0,
+            memorized,
+        };
+        let outcomes = vec![
+            outcome(0.8, 5.0, 80.0),
+            outcome(0.85, 8.0, 85.0),
+            outcome(0.9, 12.0, 90.0),
+        ];
+
+        let mut options = SimulationOptions {
+            minutes_per_day: Some(10.0),
+            ..Default::default()
+        };
+        assert_eq!(recommend(&outcomes, &options), (1, true));
+
+        options.minutes_per_day = Some(1.0);
+        assert_eq!(recommend(&outcomes, &options), (0, false));
+    }
+
+    #[test]
+    fn rejects_empty_collection() {
+        let result = simulate_workload(
+            &[],
+            &[],
+            &DEFAULT_PARAMETERS,
+            &SimulationOptions::default(),
+            Utc::now(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn new_cards_are_not_existing_simulator_cards() {
+        let now = Utc::now();
+        let rows = collection(now);
+        let cards = existing_simulator_cards(&rows.iter().collect::<Vec<_>>(), now);
+        assert_eq!(cards.len(), 20);
+        assert!(cards.iter().all(|card| card.last_date < 0.0));
+        assert!(cards.iter().all(|card| card.due >= 0.0));
+    }
+}
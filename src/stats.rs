@@ -3,16 +3,24 @@ use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 use crate::card::Card;
+use crate::crud::limits::{DailyLimits, StudiedToday};
 use crate::crud::stats::CardStatsRow;
 use crate::fsrs::LEARN_AHEAD_THRESHOLD_MINS;
-use fsrs::{FSRS6_DEFAULT_DECAY, MemoryState, current_retrievability};
+use crate::utils::DayBoundary;
+use fsrs::{MemoryState, current_retrievability};
 
 #[derive(Debug, Default)]
 pub struct CardStats {
+    /// Study-day boundary used for "due today" and the upcoming-week buckets.
+    pub day: DayBoundary,
     pub total_cards_in_db: i64,
     pub num_cards: i64,
     pub card_lifecycles: HashMap<CardLifeCycle, i64>,
     pub due_cards: i64,
+    pub suspended_cards: i64,
+    pub buried_cards: i64,
+    pub studied_today: StudiedToday,
+    pub daily_limits: DailyLimits,
     pub upcoming_week: BTreeMap<String, usize>,
     pub upcoming_month: i64,
     pub next_due_date: Option<chrono::DateTime<chrono::Utc>>,
@@ -65,8 +73,18 @@ pub enum CardLifeCycle {
 const MATURE_INTERVAL: f64 = 21.0;
 
 impl CardStats {
-    // row is a Record
-    pub fn update(&mut self, card: &Card, row: &CardStatsRow) {
+    // row is a Record; decay comes from the FSRS parameters in use
+    pub fn update(&mut self, card: &Card, row: &CardStatsRow, decay: f32) {
+        self.update_at(card, row, decay, chrono::Utc::now());
+    }
+
+    fn update_at(
+        &mut self,
+        card: &Card,
+        row: &CardStatsRow,
+        decay: f32,
+        now: chrono::DateTime<chrono::Utc>,
+    ) {
         let review_count = row.review_count;
         let due_date = row.due_date;
         let interval = row.interval_raw.unwrap_or_default();
@@ -74,9 +92,10 @@ impl CardStats {
         let stability = row.stability.unwrap_or_default();
         let last_reviewed_at = row.last_reviewed_at;
 
-        let now = chrono::Utc::now();
-        let week_horizon = now + chrono::Duration::days(7);
-        let month_horizon = now + chrono::Duration::days(30);
+        let today = self.day.study_day(now).format("%Y-%m-%d").to_string();
+        let next_day = self.day.start_of_next_day(now);
+        let week_horizon = next_day + chrono::Duration::days(6);
+        let month_horizon = next_day + chrono::Duration::days(29);
         *self.file_paths.entry(card.file_path.clone()).or_insert(0) += 1;
 
         let lifecycle = if review_count == 0 {
@@ -89,18 +108,27 @@ impl CardStats {
 
         *self.card_lifecycles.entry(lifecycle).or_insert(0) += 1;
 
+        let buried = row.buried_until.is_some_and(|until| until > now);
+        if row.suspended {
+            self.suspended_cards += 1;
+        } else if buried {
+            self.buried_cards += 1;
+        }
+
         match due_date {
+            _ if row.suspended || buried => {}
             None => {
                 self.due_cards += 1;
-                let day = now.format("%Y-%m-%d").to_string();
-                *self.upcoming_week.entry(day).or_insert(0) += 1;
+                *self.upcoming_week.entry(today).or_insert(0) += 1;
                 self.upcoming_month += 1;
             }
             Some(due_date) => {
-                if due_date <= now + LEARN_AHEAD_THRESHOLD_MINS {
+                // review cards are due for their whole study day, learning steps only when close
+                let due_now = due_date <= now + LEARN_AHEAD_THRESHOLD_MINS
+                    || (row.phase == "review" && due_date < next_day);
+                if due_now {
                     self.due_cards += 1;
-                    let day = now.format("%Y-%m-%d").to_string();
-                    *self.upcoming_week.entry(day).or_insert(0) += 1;
+                    *self.upcoming_week.entry(today).or_insert(0) += 1;
                     self.upcoming_month += 1;
                 } else {
                     match self.next_due_date {
@@ -111,12 +139,12 @@ impl CardStats {
                         _ => {}
                     }
 
-                    if due_date <= week_horizon {
-                        let day = due_date.format("%Y-%m-%d").to_string();
+                    if due_date < week_horizon {
+                        let day = self.day.study_day(due_date).format("%Y-%m-%d").to_string();
                         *self.upcoming_week.entry(day).or_insert(0) += 1;
                     }
 
-                    if due_date <= month_horizon {
+                    if due_date < month_horizon {
                         self.upcoming_month += 1;
                     }
                 }
@@ -137,7 +165,7 @@ impl CardStats {
                 difficulty: difficulty as f32,
             },
             elapsed_days.max(0.0) as f32,
-            FSRS6_DEFAULT_DECAY,
+            decay,
         ) as f64;
         self.retrievability_histogram.update(retrievabiliity);
     }
@@ -147,7 +175,8 @@ impl CardStats {
 mod tests {
     use super::*;
     use crate::card::{Card, CardContent};
-    use chrono::{Duration, Utc};
+    use chrono::{Duration, NaiveDate, Utc};
+    use fsrs::FSRS6_DEFAULT_DECAY;
     use std::path::PathBuf;
 
     fn sample_card(path: &str) -> Card {
@@ -157,6 +186,8 @@ mod tests {
             CardContent::Basic {
                 question: "Q".into(),
                 answer: "A".into(),
+                hint: None,
+                notes: None,
             },
             "hash".into(),
         )
@@ -171,6 +202,9 @@ mod tests {
             difficulty: None,
             stability: None,
             last_reviewed_at: None,
+            suspended: false,
+            buried_until: None,
+            phase: "learning".into(),
         }
     }
 
@@ -181,7 +215,7 @@ mod tests {
         let mut row = default_row();
         row.difficulty = Some(5.0);
 
-        stats.update(&card, &row);
+        stats.update(&card, &row, FSRS6_DEFAULT_DECAY);
 
         assert_eq!(*stats.card_lifecycles.get(&CardLifeCycle::New).unwrap(), 1);
         assert_eq!(stats.due_cards, 1);
@@ -200,7 +234,7 @@ mod tests {
         row.interval_raw = Some(30.0);
         row.due_date = Some(Utc::now() + Duration::days(3));
 
-        stats.update(&card, &row);
+        stats.update(&card, &row, FSRS6_DEFAULT_DECAY);
 
         assert_eq!(
             *stats.card_lifecycles.get(&CardLifeCycle::Mature).unwrap(),
@@ -211,6 +245,31 @@ mod tests {
         assert_eq!(stats.upcoming_week.values().sum::<usize>(), 1);
     }
 
+    #[test]
+    fn review_cards_are_due_for_the_rest_of_the_study_day() {
+        let mut stats = CardStats::default();
+        let card = sample_card("deck/file.md");
+        // midday, well clear of the rollover
+        let now = stats
+            .day
+            .start_of(NaiveDate::from_ymd_opt(2026, 3, 10).unwrap())
+            + Duration::hours(8);
+        let end_of_day = stats.day.start_of_next_day(now) - Duration::minutes(1);
+        let mut row = default_row();
+        row.review_count = 3;
+        row.due_date = Some(end_of_day);
+
+        stats.update_at(&card, &row, FSRS6_DEFAULT_DECAY, now);
+        assert_eq!(stats.due_cards, 0);
+
+        row.phase = "review".into();
+        stats.update_at(&card, &row, FSRS6_DEFAULT_DECAY, now);
+        assert_eq!(stats.due_cards, 1);
+        // both land in today's bucket: one later today, one due now
+        let today = stats.day.study_day(now).format("%Y-%m-%d").to_string();
+        assert_eq!(stats.upcoming_week.get(&today), Some(&2));
+    }
+
     #[test]
     fn updates_retrievability_histogram_when_reviewed() {
         let mut stats = CardStats::default();
@@ -221,7 +280,7 @@ mod tests {
         row.stability = Some(5.0);
         row.last_reviewed_at = Some(Utc::now() - Duration::days(4));
 
-        stats.update(&card, &row);
+        stats.update(&card, &row, FSRS6_DEFAULT_DECAY);
 
         let recall = current_retrievability(
             MemoryState {
@@ -265,7 +324,7 @@ mod tests {
         row.difficulty = Some(7.5);
         row.last_reviewed_at = None; // Card has never been reviewed
 
-        stats.update(&card, &row);
+        stats.update(&card, &row, FSRS6_DEFAULT_DECAY);
 
         // Difficulty histogram should remain empty
         assert_eq!(stats.difficulty_histogram.bins.iter().sum::<u32>(), 0);
@@ -282,7 +341,7 @@ mod tests {
         row.stability = Some(10.0);
         row.last_reviewed_at = Some(Utc::now() - Duration::days(2));
 
-        stats.update(&card, &row);
+        stats.update(&card, &row, FSRS6_DEFAULT_DECAY);
 
         // Difficulty histogram should be updated (7.5 / 10.0 = 0.75)
         let total_count: u32 = stats.difficulty_histogram.bins.iter().sum();
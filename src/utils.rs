@@ -2,7 +2,8 @@ use std::path::Path;
 
 use anyhow::Result;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
 use dialoguer::Confirm;
 use dialoguer::theme::ColorfulTheme;
 use directories::ProjectDirs;
@@ -77,6 +78,81 @@ pub fn ask_yn(prompt: String) -> bool {
         .unwrap()
 }
 
+pub const DEFAULT_ROLLOVER_HOUR: u32 = 4;
+
+/// Where one study day ends and the next begins: `rollover_hour` o'clock local time.
+///
+/// Reviewing at 1am with a 4am rollover still counts towards the previous day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DayBoundary {
+    pub rollover_hour: u32,
+}
+
+impl Default for DayBoundary {
+    fn default() -> Self {
+        Self {
+            rollover_hour: DEFAULT_ROLLOVER_HOUR,
+        }
+    }
+}
+
+impl DayBoundary {
+    pub fn new(rollover_hour: u32) -> Result<Self> {
+        if rollover_hour > 23 {
+            bail!("day_rollover_hour must be between 0 and 23, got {rollover_hour}");
+        }
+        Ok(Self { rollover_hour })
+    }
+
+    /// The study day `at` belongs to.
+    pub fn study_day(&self, at: DateTime<Utc>) -> NaiveDate {
+        self.study_day_in(at, &Local)
+    }
+
+    /// When the study day `now` belongs to started.
+    pub fn start_of_today(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        self.start_of_day_in(self.study_day_in(now, &Local), &Local)
+    }
+
+    /// When the study day after the one `now` belongs to starts.
+    pub fn start_of_next_day(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        self.start_of_next_day_in(now, &Local)
+    }
+
+    /// When the study day `day` starts.
+    pub fn start_of(&self, day: NaiveDate) -> DateTime<Utc> {
+        self.start_of_day_in(day, &Local)
+    }
+
+    /// Whole study days from `now` until `at`; zero means later today.
+    pub fn days_until(&self, now: DateTime<Utc>, at: DateTime<Utc>) -> i64 {
+        (self.study_day(at) - self.study_day(now)).num_days()
+    }
+
+    fn study_day_in<Tz: TimeZone>(&self, at: DateTime<Utc>, tz: &Tz) -> NaiveDate {
+        (at.with_timezone(tz).naive_local() - Duration::hours(i64::from(self.rollover_hour))).date()
+    }
+
+    fn start_of_next_day_in<Tz: TimeZone>(&self, now: DateTime<Utc>, tz: &Tz) -> DateTime<Utc> {
+        let tomorrow = self.study_day_in(now, tz) + Duration::days(1);
+        self.start_of_day_in(tomorrow, tz)
+    }
+
+    /// When a DST change skips the rollover time on `day`, the day starts at the first
+    /// whole hour after it that exists. A repeated rollover time counts from its first pass.
+    fn start_of_day_in<Tz: TimeZone>(&self, day: NaiveDate, tz: &Tz) -> DateTime<Utc> {
+        let rollover = NaiveTime::from_hms_opt(self.rollover_hour, 0, 0).unwrap_or(NaiveTime::MIN);
+        let rollover = day.and_time(rollover);
+        (0..=24)
+            .find_map(|hours| {
+                (rollover + Duration::hours(hours))
+                    .and_local_timezone(tz.clone())
+                    .earliest()
+            })
+            .map_or_else(|| rollover.and_utc(), |start| start.with_timezone(&Utc))
+    }
+}
+
 pub fn get_data_dir() -> Result<std::path::PathBuf> {
     let proj_dirs = ProjectDirs::from("", "", "repeater")
         .ok_or_else(|| anyhow!("Could not determine project directory"))?;
@@ -116,6 +192,79 @@ mod tests {
         assert_eq!(pluralize("cloze card", 3), "3 cloze cards");
     }
 
+    #[test]
+    fn next_day_starts_at_the_rollover_hour() {
+        let now = Utc::now();
+        let next = DayBoundary::default().start_of_next_day(now);
+        assert!(next > now);
+        assert!(next <= now + Duration::days(1) + Duration::hours(1));
+        assert_eq!(
+            next.with_timezone(&Local).time(),
+            NaiveTime::from_hms_opt(DEFAULT_ROLLOVER_HOUR, 0, 0).unwrap()
+        );
+
+        let today = DayBoundary::default().start_of_today(now);
+        assert!(today <= now && today > now - Duration::days(1) - Duration::hours(1));
+
+        let midnight = DayBoundary::new(0).unwrap().start_of_next_day(now);
+        assert_eq!(midnight.with_timezone(&Local).time(), NaiveTime::MIN);
+        assert!(DayBoundary::new(24).is_err());
+    }
+
+    #[test]
+    fn study_days_follow_the_local_rollover() {
+        use chrono::FixedOffset;
+
+        // UTC-5: 02:00 local on the 11th is still the study day of the 10th
+        let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+        let boundary = DayBoundary::new(4).unwrap();
+        let at = |day, hour| {
+            tz.with_ymd_and_hms(2025, 3, day, hour, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let date = |day| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+
+        assert_eq!(boundary.study_day_in(at(11, 2), &tz), date(10));
+        assert_eq!(boundary.study_day_in(at(11, 4), &tz), date(11));
+        assert_eq!(boundary.study_day_in(at(11, 23), &tz), date(11));
+        assert_eq!(boundary.start_of_next_day_in(at(11, 2), &tz), at(11, 4));
+        assert_eq!(boundary.start_of_next_day_in(at(11, 20), &tz), at(12, 4));
+        assert_eq!(boundary.start_of_day_in(date(10), &tz), at(10, 4));
+    }
+
+    #[test]
+    fn study_days_start_after_a_skipped_rollover() {
+        use chrono_tz::America::New_York;
+
+        // clocks jump from 02:00 to 03:00 on 9 March 2025, so that day starts at 03:00
+        let boundary = DayBoundary::new(2).unwrap();
+        let local = |day, hour| {
+            New_York
+                .with_ymd_and_hms(2025, 3, day, hour, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let date = |day| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+
+        assert_eq!(boundary.start_of_day_in(date(9), &New_York), local(9, 3));
+        assert_eq!(
+            boundary.start_of_next_day_in(local(8, 20), &New_York),
+            local(9, 3)
+        );
+        assert_eq!(
+            boundary.start_of_next_day_in(local(9, 12), &New_York),
+            local(10, 2)
+        );
+        // 01:00 comes round twice on 2 November 2025; the day starts at the first, in EDT
+        assert_eq!(
+            DayBoundary::new(1)
+                .unwrap()
+                .start_of_day_in(NaiveDate::from_ymd_opt(2025, 11, 2).unwrap(), &New_York),
+            Utc.with_ymd_and_hms(2025, 11, 2, 5, 0, 0).unwrap()
+        );
+    }
+
     #[test]
     fn test_pluralize_zero() {
         assert_eq!(pluralize("card", 0), "0 cards");